@@ -0,0 +1,109 @@
+use crate::types::*;
+use crate::bitboard::*;
+use crate::movegen::attack_tables::ATTACK_TABLE;
+use crate::position::Position;
+
+// attack queries. by_color / co is always the side doing the attacking, whoever is to move. squares occupied by the
+// attacker's own pieces count as attacked (defended), and en passant is never counted as an attack
+
+impl Position {
+    // pieces of by_color attacking sq. sliders look through the given occupancy instead of the board's, so pieces
+    // can be taken off or put on the board without changing the position
+    pub fn attackers_to(&self, sq: Square, by_color: Color, occupied: Bitboard) -> Bitboard {
+        let attackers: Bitboard = (ATTACK_TABLE.get_pawn_attacks(sq, !by_color) & self.pawns) |
+            (ATTACK_TABLE.get_jump_attacks(sq, Piece::Knight) & self.knights) |
+            (ATTACK_TABLE.get_jump_attacks(sq, Piece::King) & self.kings) |
+            (ATTACK_TABLE.get_sliding_attacks(sq, Piece::Bishop, occupied) & (self.bishops | self.queens)) |
+            (ATTACK_TABLE.get_sliding_attacks(sq, Piece::Rook, occupied) & (self.rooks | self.queens));
+
+        attackers & self.occupied[by_color.index()]
+    }
+
+    // every square attacked by a piece of color co
+    pub fn attacked_squares(&self, co: Color) -> Bitboard {
+        let occupied: Bitboard = self.occupied[Color::White.index()] | self.occupied[Color::Black.index()];
+        let own: Bitboard = self.occupied[co.index()];
+
+        // pawns attack all at once, diagonally forward
+        let pawns: Bitboard = self.pawns & own;
+        let mut attacked: Bitboard = if co == Color::White {
+            ((pawns & !BB_FILE_A) << 7) | ((pawns & !BB_FILE_H) << 9)
+        } else {
+            ((pawns & !BB_FILE_H) >> 7) | ((pawns & !BB_FILE_A) >> 9)
+        };
+
+        for (pt, pieces) in [(Piece::Knight, self.knights), (Piece::Bishop, self.bishops), (Piece::Rook, self.rooks), (Piece::Queen, self.queens), (Piece::King, self.kings)] {
+            let mut piece_bb: Bitboard = pieces & own;
+            while piece_bb > 0 {
+                let sq: Square = pop_lsb(&mut piece_bb);
+                attacked |= match pt {
+                    Piece::Knight | Piece::King => ATTACK_TABLE.get_jump_attacks(sq, pt),
+                    _ => ATTACK_TABLE.get_sliding_attacks(sq, pt, occupied)
+                };
+            }
+        }
+
+        attacked
+    }
+
+    // whether the side to move is in check
+    #[inline]
+    pub fn is_check(&self) -> bool {
+        self.checkers > 0
+    }
+
+    // sliders of by_color that would attack sq if the first piece in their way, of either color, were removed
+    pub fn xray_attackers(&self, sq: Square, by_color: Color) -> Bitboard {
+        let occupied: Bitboard = self.occupied[Color::White.index()] | self.occupied[Color::Black.index()];
+        let mut xrays: Bitboard = BB_NONE;
+
+        for (pt, sliders) in [(Piece::Bishop, self.bishops | self.queens), (Piece::Rook, self.rooks | self.queens)] {
+            let direct: Bitboard = ATTACK_TABLE.get_sliding_attacks(sq, pt, occupied);
+            let through_blockers: Bitboard = ATTACK_TABLE.get_sliding_attacks(sq, pt, occupied & !direct);
+            xrays |= through_blockers & !direct & sliders;
+        }
+
+        xrays & self.occupied[by_color.index()]
+    }
+}
+
+// check the attack queries against each other and against brute force, on the perft positions and every position one
+// move away from them
+pub fn check_attacks_correctness() {
+    for p_res in crate::perft::PERFT_RESULTS {
+        let root = Position::from_fen(p_res.fen).unwrap();
+        check_attacks_in(&root);
+        for mv in root.legal_moves().iter() {
+            let mut updated_pos = root;
+            updated_pos.make(mv);
+            check_attacks_in(&updated_pos);
+        }
+        println!("Attacks '{}' passed", p_res.fen);
+    }
+    println!("Attacks passed");
+}
+
+fn check_attacks_in(pos: &Position) {
+    let occupied: Bitboard = pos.occupied[Color::White.index()] | pos.occupied[Color::Black.index()];
+    let king: Square = lsb(pos.kings & pos.occupied[pos.turn.index()]);
+    assert_eq!(pos.is_check(), pos.attackers_to(king, !pos.turn, occupied) > 0, "check mismatch in {}", pos.to_fen());
+
+    for co in Color::ALL {
+        let attacked: Bitboard = pos.attacked_squares(co);
+        let sliders: Bitboard = (pos.bishops | pos.rooks | pos.queens) & pos.occupied[co.index()];
+
+        for sq in Square::iter() {
+            let direct: Bitboard = pos.attackers_to(sq, co, occupied);
+            assert_eq!(attacked & square_bb(sq) > 0, direct > 0, "{} attacks on {} wrong in {}", co, sq, pos.to_fen());
+
+            // an x-ray attacker is a slider that starts attacking once a single piece is taken off the board
+            let mut xrays: Bitboard = BB_NONE;
+            let mut blockers: Bitboard = occupied;
+            while blockers > 0 {
+                let blocker: Square = pop_lsb(&mut blockers);
+                xrays |= pos.attackers_to(sq, co, occupied & !square_bb(blocker)) & !direct & sliders & !square_bb(blocker);
+            }
+            assert_eq!(pos.xray_attackers(sq, co), xrays, "{} x-rays on {} wrong in {}", co, sq, pos.to_fen());
+        }
+    }
+}
@@ -6,7 +6,9 @@ pub const BB_NONE: Bitboard = 0x0;
 pub const BB_ONE: Bitboard = 0x1;
 pub const BB_ALL: Bitboard = 0xffffffffffffffff;
 
+#[allow(clippy::erasing_op, clippy::identity_op)]
 pub const BB_RANK_1: Bitboard = 0xff << (8*0);
+#[allow(clippy::identity_op)]
 pub const BB_RANK_2: Bitboard = 0xff << (8*1);
 pub const BB_RANK_3: Bitboard = 0xff << (8*2);
 pub const BB_RANK_4: Bitboard = 0xff << (8*3);
@@ -15,6 +17,7 @@ pub const BB_RANK_6: Bitboard = 0xff << (8*5);
 pub const BB_RANK_7: Bitboard = 0xff << (8*6);
 pub const BB_RANK_8: Bitboard = 0xff << (8*7);
 
+#[allow(clippy::identity_op)]
 pub const BB_FILE_A: Bitboard = 0x101010101010101 << 0;
 pub const BB_FILE_B: Bitboard = 0x101010101010101 << 1;
 pub const BB_FILE_C: Bitboard = 0x101010101010101 << 2;
@@ -24,26 +27,25 @@ pub const BB_FILE_F: Bitboard = 0x101010101010101 << 5;
 pub const BB_FILE_G: Bitboard = 0x101010101010101 << 6;
 pub const BB_FILE_H: Bitboard = 0x101010101010101 << 7;
 
+pub const BB_DARK_SQUARES: Bitboard = 0xaa55aa55aa55aa55;
+pub const BB_LIGHT_SQUARES: Bitboard = !BB_DARK_SQUARES;
+
 pub const BB_DIAG_ASC_0: Bitboard = 0x8040201008040201;
 pub const BB_DIAG_DESC_0: Bitboard = 0x102040810204080;
 
-// pieces on these squares prevent castling
-pub const CASTLE_BLOCKER_MASK_KINGSIDE: Bitboard = (BB_FILE_F | BB_FILE_G) & (BB_RANK_1 | BB_RANK_8);
-pub const CASTLE_BLOCKER_MASK_QUEENSIDE: Bitboard = (BB_FILE_B | BB_FILE_C | BB_FILE_D) & (BB_RANK_1 | BB_RANK_8);
-
 #[inline]
 pub const fn square_bb(sq: Square) -> Bitboard {
-    BB_ONE << sq
+    BB_ONE << sq.index()
 }
 
 #[inline]
 pub const fn rank_bb(sq: Square) -> Bitboard {
-    0xff << 8 * square_rank(sq)
+    0xff << (8 * sq.rank())
 }
 
 #[inline]
 pub const fn file_bb(sq: Square) -> Bitboard {
-    0x101010101010101 << square_file(sq)
+    0x101010101010101 << sq.file()
 }
 
 #[inline]
@@ -68,12 +70,12 @@ pub const fn diag_desc_bb(sq: Square) -> Bitboard {
 
 #[inline]
 pub fn lsb(bb: Bitboard) -> Square {
-    bb.trailing_zeros() as Square
+    Square::new_unchecked(bb.trailing_zeros() as u8)
 }
 
 #[inline]
 pub fn msb(bb: Bitboard) -> Square {
-    bb.leading_zeros() as Square
+    Square::new_unchecked(63 - bb.leading_zeros() as u8)
 }
 
 #[inline]
@@ -88,24 +90,25 @@ pub fn popcount(bb: Bitboard) -> u8 {
     bb.count_ones() as u8
 }
 
-#[inline]
-pub const fn square_rank(sq: Square) -> u8 {
-    sq as u8 >> 3
-}
-
-#[inline]
-pub const fn square_file(sq: Square) -> u8 {
-    sq as u8 & 7
-}
-
 // amount of king moves from sq1 to sq2
 #[inline]
 pub fn chebyshev_distance(sq1: Square, sq2: Square) -> u8 {
-    let rank_diff = square_rank(sq2) as i8 - square_rank(sq1) as i8;
-    let file_diff = square_file(sq2) as i8 - square_file(sq1) as i8;
+    let rank_diff = sq2.rank() as i8 - sq1.rank() as i8;
+    let file_diff = sq2.file() as i8 - sq1.file() as i8;
     cmp::max(rank_diff.abs(), file_diff.abs()) as u8
 }
 
+// squares the king and rook end up on when castling with the rook on rook_sq. this is the same for standard chess and chess960
+#[inline]
+pub const fn castling_targets(king_sq: Square, rook_sq: Square) -> (Square, Square) {
+    let rank: u8 = rook_sq.rank();
+    if rook_sq.index() > king_sq.index() {
+        (Square::from_coords_unchecked(6, rank), Square::from_coords_unchecked(5, rank))
+    } else {
+        (Square::from_coords_unchecked(2, rank), Square::from_coords_unchecked(3, rank))
+    }
+}
+
 pub fn print_bitboard(bb: Bitboard) {
     for i in (0..8).rev() {
         for j in 0..8 {
@@ -128,14 +131,12 @@ pub const SQUARE_NAMES: [&str; 64] = [
     "a8", "b8", "c8", "d8", "e8", "f8", "g8", "h8",
 ];
 
-pub const PIECE_NAMES: [&str; 7] = ["Pawn", "Knight", "Bishop", "Rook", "Queen", "King", "None"];
-
 #[inline]
 const fn square_diag_asc(sq: Square) -> i8 {
-    square_rank(sq) as i8 - square_file(sq) as i8
+    sq.rank() as i8 - sq.file() as i8
 }
 
 #[inline]
 const fn square_diag_desc(sq: Square) -> i8 {
-    square_rank(sq) as i8 + square_file(sq) as i8 - 7
+    sq.rank() as i8 + sq.file() as i8 - 7
 }
@@ -0,0 +1,296 @@
+use std::fmt;
+
+use crate::types::*;
+use crate::bitboard::*;
+use crate::position::Position;
+
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+// error returned when a FEN string can't be parsed. offset is the byte offset into the FEN where parsing failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FenError {
+    Board { offset: usize },
+    Side { offset: usize },
+    Castling { offset: usize },
+    EnPassant { offset: usize },
+    HalfmoveClock { offset: usize },
+    FullmoveNumber { offset: usize },
+    TrailingData { offset: usize },
+}
+
+impl FenError {
+    pub fn offset(&self) -> usize {
+        match *self {
+            FenError::Board { offset } |
+            FenError::Side { offset } |
+            FenError::Castling { offset } |
+            FenError::EnPassant { offset } |
+            FenError::HalfmoveClock { offset } |
+            FenError::FullmoveNumber { offset } |
+            FenError::TrailingData { offset } => offset
+        }
+    }
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let field = match self {
+            FenError::Board { .. } => "piece placement",
+            FenError::Side { .. } => "side to move",
+            FenError::Castling { .. } => "castling rights",
+            FenError::EnPassant { .. } => "en passant square",
+            FenError::HalfmoveClock { .. } => "halfmove clock",
+            FenError::FullmoveNumber { .. } => "fullmove number",
+            FenError::TrailingData { .. } => "trailing data"
+        };
+        write!(f, "invalid FEN: bad {} at byte {}", field, self.offset())
+    }
+}
+
+impl std::error::Error for FenError {}
+
+impl Position {
+    // parse a position from a FEN string. missing trailing fields default to white to play, no castling rights,
+    // no en passant square and move counters of 0 and 1
+    pub fn from_fen(fen: &str) -> Result<Position, FenError> {
+        let mut pos = Position::default();
+        pos.clear();
+        pos.fullmove_count = 1;
+
+        // split into fields, remembering where each one starts
+        let mut fields = fen.split_ascii_whitespace().map(|field| (field.as_ptr() as usize - fen.as_ptr() as usize, field));
+
+        // parse piece positions
+        let (offset, board) = fields.next().ok_or(FenError::Board { offset: 0 })?;
+        pos.parse_board(board, offset)?;
+
+        // set turn
+        match fields.next() {
+            Some((_, "w")) => pos.turn = Color::White,
+            Some((_, "b")) => pos.turn = Color::Black,
+            Some((offset, _)) => return Err(FenError::Side { offset }),
+            None => {
+                pos.hash = pos.compute_hash_from_scratch();
+                pos.update_check_info();
+                return Ok(pos)
+            }
+        }
+
+        // set castling rights. K and Q stand for the outermost rook on that side of the king, files (Shredder-FEN and X-FEN)
+        // pick out a specific rook for chess960
+        if let Some((offset, castling)) = fields.next() {
+            if castling != "-" {
+                for (i, ch) in castling.char_indices() {
+                    let co: Color = if ch.is_ascii_uppercase() {Color::White} else {Color::Black};
+                    let rook_square: Square = match ch.to_ascii_lowercase() {
+                        'k' => pos.outermost_rook(co, true),
+                        'q' => pos.outermost_rook(co, false),
+                        file @ 'a'..='h' => Square::from_coords_unchecked(file as u8 - b'a', if co == Color::White {0} else {7}),
+                        _ => return Err(FenError::Castling { offset: offset + i })
+                    };
+                    pos.castling_rights |= square_bb(rook_square);
+                }
+
+                pos.chess960 = !pos.has_standard_castling_rights();
+            }
+        }
+
+        // set en passant square, dropping it if no pawn can take there, the same as make does
+        if let Some((offset, ep)) = fields.next() {
+            if ep != "-" {
+                let ep_square: Square = ep.parse().map_err(|_| FenError::EnPassant { offset })?;
+                if pos.ep_square_capturable(ep_square, pos.turn) {
+                    pos.ep_square = Some(ep_square);
+                }
+            }
+        }
+
+        // set halfmove count
+        if let Some((offset, halfmove_str)) = fields.next() {
+            pos.halfmove_count = halfmove_str.parse().map_err(|_| FenError::HalfmoveClock { offset })?;
+        }
+
+        // set fullmove count
+        if let Some((offset, fullmove_str)) = fields.next() {
+            pos.fullmove_count = fullmove_str.parse().map_err(|_| FenError::FullmoveNumber { offset })?;
+        }
+
+        if let Some((offset, _)) = fields.next() {
+            return Err(FenError::TrailingData { offset });
+        }
+
+        pos.hash = pos.compute_hash_from_scratch();
+        pos.update_check_info();
+        Ok(pos)
+    }
+
+    // write the position as a FEN string
+    pub fn to_fen(&self) -> String {
+        format!("{} {} {}", self.to_epd(), self.halfmove_count, self.fullmove_count)
+    }
+
+    // write the position as an EPD string, which is a FEN without the move counters
+    pub fn to_epd(&self) -> String {
+        format!("{} {} {} {}", self.board_fen(), if self.turn == Color::White {'w'} else {'b'}, self.castling_fen(), self.ep_fen())
+    }
+
+    // piece placement field of the FEN
+    pub fn board_fen(&self) -> String {
+        let mut fen = String::new();
+
+        for rank in (0..8).rev() {
+            let mut empty: u8 = 0;
+            for file in 0..8 {
+                let sq: Square = Square::from_coords_unchecked(file, rank);
+                let Some(piece_type) = self.piece_at(sq) else {
+                    empty += 1;
+                    continue;
+                };
+
+                if empty > 0 {
+                    fen.push((b'0' + empty) as char);
+                    empty = 0;
+                }
+
+                let piece_char: char = piece_type.symbol();
+                fen.push(if self.color_at(sq) == Some(Color::White) {piece_char} else {piece_char.to_ascii_lowercase()});
+            }
+
+            if empty > 0 {
+                fen.push((b'0' + empty) as char);
+            }
+            if rank > 0 {
+                fen.push('/');
+            }
+        }
+
+        fen
+    }
+
+    // castling rights field of the FEN. rights for rooks that aren't the outermost one on their side are written
+    // as files, like X-FEN
+    pub fn castling_fen(&self) -> String {
+        let mut fen = String::new();
+
+        for co in Color::ALL {
+            let backrank: Bitboard = if co == Color::White {BB_RANK_1} else {BB_RANK_8};
+            let king: Square = self.backrank_king(co);
+
+            // kingside rights first
+            let mut rights: Vec<Square> = Vec::new();
+            let mut castling_squares: Bitboard = self.castling_rights & backrank;
+            while castling_squares > 0 {
+                rights.push(pop_lsb(&mut castling_squares));
+            }
+
+            for &sq in rights.iter().rev() {
+                let kingside: bool = sq > king;
+                let ch: char = if sq == self.outermost_rook(co, kingside) {
+                    if kingside {'k'} else {'q'}
+                } else {
+                    (b'a' + sq.file()) as char
+                };
+                fen.push(if co == Color::White {ch.to_ascii_uppercase()} else {ch});
+            }
+        }
+
+        if fen.is_empty() {
+            fen.push('-');
+        }
+        fen
+    }
+
+    // square of the king on its back rank, or where it starts in standard chess if it isn't there
+    fn backrank_king(&self, co: Color) -> Square {
+        let backrank: Bitboard = if co == Color::White {BB_RANK_1} else {BB_RANK_8};
+        let king_bb: Bitboard = self.kings & self.occupied[co.index()] & backrank;
+        if king_bb > 0 {
+            lsb(king_bb)
+        } else if co == Color::White {
+            Square::E1
+        } else {
+            Square::E8
+        }
+    }
+
+    // square of the rook furthest from the king on one side of it, or the corner if there is no rook there
+    fn outermost_rook(&self, co: Color, kingside: bool) -> Square {
+        let backrank: Bitboard = if co == Color::White {BB_RANK_1} else {BB_RANK_8};
+        let king: Square = self.backrank_king(co);
+
+        let side: Bitboard = if kingside {BB_ALL.checked_shl(king.index() as u32 + 1).unwrap_or(BB_NONE)} else {square_bb(king) - 1};
+        let rooks: Bitboard = self.rooks & self.occupied[co.index()] & backrank & side;
+
+        match (rooks > 0, kingside) {
+            (true, true) => msb(rooks),
+            (true, false) => lsb(rooks),
+            (false, true) => lsb(backrank & BB_FILE_H),
+            (false, false) => lsb(backrank & BB_FILE_A)
+        }
+    }
+
+    // whether every castling right is for a corner rook with the king on the e-file
+    fn has_standard_castling_rights(&self) -> bool {
+        let corners: Bitboard = (BB_RANK_1 | BB_RANK_8) & (BB_FILE_A | BB_FILE_H);
+        let white_standard: bool = self.castling_rights & BB_RANK_1 == 0 || (self.kings & self.occupied[Color::White.index()] & square_bb(Square::E1) > 0);
+        let black_standard: bool = self.castling_rights & BB_RANK_8 == 0 || (self.kings & self.occupied[Color::Black.index()] & square_bb(Square::E8) > 0);
+
+        self.castling_rights & !corners == 0 && white_standard && black_standard
+    }
+
+    // en passant field of the FEN
+    pub fn ep_fen(&self) -> &'static str {
+        self.ep_square.map_or("-", Square::name)
+    }
+
+    // parse a FEN into this position, leaving it untouched if the FEN is invalid
+    pub fn parse_fen(&mut self, fen: &str) -> Result<(), FenError> {
+        *self = Position::from_fen(fen)?;
+        Ok(())
+    }
+
+    // parse the piece placement field. board_offset is where the field starts in the full FEN
+    fn parse_board(&mut self, board: &str, board_offset: usize) -> Result<(), FenError> {
+        let mut rank: u8 = 7;
+        let mut file: u8 = 0;
+
+        for (i, ch) in board.char_indices() {
+            let offset = board_offset + i;
+
+            if ch == '/' {
+                // every rank must be complete before moving on to the next one
+                if file != 8 || rank == 0 {
+                    return Err(FenError::Board { offset });
+                }
+                rank -= 1;
+                file = 0;
+                continue;
+            }
+
+            if let Some(empty) = ch.to_digit(10) {
+                if !(1..=8).contains(&empty) || file + empty as u8 > 8 {
+                    return Err(FenError::Board { offset });
+                }
+                file += empty as u8;
+                continue;
+            }
+
+            if file >= 8 {
+                return Err(FenError::Board { offset });
+            }
+
+            let piece_type: Piece = Piece::from_symbol(ch).ok_or(FenError::Board { offset })?;
+            let piece_color: Color = if ch.is_ascii_uppercase() {Color::White} else {Color::Black};
+
+            self.set_piece_at(Square::from_coords_unchecked(file, rank), piece_type, piece_color);
+            file += 1;
+        }
+
+        // all eight ranks must be present
+        if rank != 0 || file != 8 {
+            return Err(FenError::Board { offset: board_offset + board.len() });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,254 @@
+use std::fmt;
+
+use crate::types::*;
+use crate::position::{Position, Undo};
+use crate::termination::Termination;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DrawReason {
+    Stalemate,
+    InsufficientMaterial,
+    FiftyMoves,
+    SeventyFiveMoves,
+    ThreefoldRepetition,
+    FivefoldRepetition,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw(DrawReason),
+    Ongoing,
+}
+
+// writes the result the way PGN does
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw(_) => "1/2-1/2",
+            GameResult::Ongoing => "*"
+        })
+    }
+}
+
+// error returned when trying to play a move that isn't legal in the current position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IllegalMove(pub Move);
+
+impl fmt::Display for IllegalMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "illegal move {}", self.0)
+    }
+}
+
+impl std::error::Error for IllegalMove {}
+
+// a game from some starting position, keeping the moves played so far and the hash of every position reached
+#[derive(Debug, Clone)]
+pub struct Game {
+    start: Position,
+    position: Position,
+
+    moves: Vec<Move>,
+    undos: Vec<Undo>,
+    // keys[i] is the hash of the position after i moves
+    keys: Vec<u64>,
+
+    // moves taken back with undo, most recent last
+    redo_moves: Vec<Move>,
+
+    claimed_draw: Option<DrawReason>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+    // new game from the standard starting position
+    pub fn new() -> Self {
+        Self::from_position(Position::new())
+    }
+
+    pub fn from_position(pos: Position) -> Self {
+        Self {
+            start: pos,
+            position: pos,
+
+            moves: Vec::new(),
+            undos: Vec::new(),
+            keys: vec![pos.hash],
+
+            redo_moves: Vec::new(),
+
+            claimed_draw: None,
+        }
+    }
+
+    #[inline]
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    #[inline]
+    pub fn start_position(&self) -> &Position {
+        &self.start
+    }
+
+    #[inline]
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    // hashes of every position in the game, starting with the start position
+    #[inline]
+    pub fn keys(&self) -> &[u64] {
+        &self.keys
+    }
+
+    // play a move, checking that it is legal first
+    pub fn make_move(&mut self, mv: &Move) -> Result<(), IllegalMove> {
+        if !self.position.legal_moves().contains(mv) {
+            return Err(IllegalMove(*mv));
+        }
+
+        self.push(mv);
+        self.redo_moves.clear();
+        Ok(())
+    }
+
+    // take back the last move, returning it. it can be replayed with redo until another move is made
+    pub fn undo(&mut self) -> Option<Move> {
+        let mv = self.moves.pop()?;
+        let undo = self.undos.pop()?;
+        self.keys.pop();
+
+        self.position.unmake(&mv, &undo);
+        self.redo_moves.push(mv);
+        self.claimed_draw = None;
+
+        Some(mv)
+    }
+
+    // replay the last move taken back with undo
+    pub fn redo(&mut self) -> Option<Move> {
+        let mv = self.redo_moves.pop()?;
+        self.push(&mv);
+
+        Some(mv)
+    }
+
+    // play a move that is already known to be legal
+    fn push(&mut self, mv: &Move) {
+        let undo = self.position.make(mv);
+
+        self.moves.push(*mv);
+        self.undos.push(undo);
+        self.keys.push(self.position.hash);
+        self.claimed_draw = None;
+    }
+}
+
+// repetitions and results
+impl Game {
+    // how many times the current position has occurred in the game, including now
+    pub fn repetition_count(&self) -> usize {
+        let current: u64 = self.position.hash;
+
+        // positions before the last pawn move or capture can't repeat, and only positions with the same side to move count
+        let reversible_plies: usize = (self.position.halfmove_count as usize).min(self.keys.len() - 1);
+        self.keys.iter()
+            .rev()
+            .take(reversible_plies + 1)
+            .step_by(2)
+            .filter(|&&key| key == current)
+            .count()
+    }
+
+    // the side to move may claim a draw
+    pub fn is_threefold_repetition(&self) -> bool {
+        self.repetition_count() >= 3
+    }
+
+    // the game is drawn automatically
+    pub fn is_fivefold_repetition(&self) -> bool {
+        self.repetition_count() >= 5
+    }
+
+    // draw the side to move could claim right now, if any
+    pub fn claimable_draw(&self) -> Option<DrawReason> {
+        if self.is_threefold_repetition() {
+            Some(DrawReason::ThreefoldRepetition)
+        } else if self.position.is_fifty_moves() {
+            Some(DrawReason::FiftyMoves)
+        } else {
+            None
+        }
+    }
+
+    // claim a draw by threefold repetition or the fifty move rule. returns the reason if the claim was valid
+    pub fn claim_draw(&mut self) -> Option<DrawReason> {
+        self.claimed_draw = self.claimable_draw();
+        self.claimed_draw
+    }
+
+    pub fn result(&self) -> GameResult {
+        match self.position.termination() {
+            Some(Termination::Checkmate) => {
+                return if self.position.turn == Color::White {GameResult::BlackWins} else {GameResult::WhiteWins};
+            }
+            Some(Termination::Stalemate) => return GameResult::Draw(DrawReason::Stalemate),
+            Some(Termination::SeventyFiveMoves) => return GameResult::Draw(DrawReason::SeventyFiveMoves),
+            Some(Termination::InsufficientMaterial) => return GameResult::Draw(DrawReason::InsufficientMaterial),
+            None => ()
+        }
+
+        if self.is_fivefold_repetition() {
+            return GameResult::Draw(DrawReason::FivefoldRepetition);
+        }
+
+        match self.claimed_draw {
+            Some(reason) => GameResult::Draw(reason),
+            None => GameResult::Ongoing
+        }
+    }
+}
+
+// play games whose repetition counts are known, checking the count after every move
+pub fn check_game_correctness() {
+    // the knights dance back to the position after 1.e4 every four plies. no black pawn can take on e3, so the
+    // position straight after 1.e4 counts as the first occurrence
+    let moves: [&str; 17] = ["e4", "Nf6", "Nf3", "Ng8", "Ng1", "Nf6", "Nf3", "Ng8", "Ng1", "Nf6", "Nf3", "Ng8", "Ng1", "Nf6", "Nf3", "Ng8", "Ng1"];
+    let counts: [usize; 17] = [1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5];
+
+    let mut game = Game::new();
+    for (san, count) in moves.into_iter().zip(counts) {
+        let mv = game.position().parse_san(san).unwrap();
+        game.make_move(&mv).unwrap();
+        assert_eq!(game.repetition_count(), count, "repetition count after {} in {}", san, game.position().to_fen());
+    }
+    assert!(game.is_threefold_repetition());
+    assert!(game.is_fivefold_repetition());
+    assert_eq!(game.result(), GameResult::Draw(DrawReason::FivefoldRepetition));
+
+    // the same dance from a FEN whose ep square no pawn can use, which has to count like the position reached by e4
+    let mut game = Game::from_position(Position::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap());
+    for (san, count) in moves[1..9].iter().zip(&counts[1..9]) {
+        let mv = game.position().parse_san(san).unwrap();
+        game.make_move(&mv).unwrap();
+        assert_eq!(game.repetition_count(), *count, "repetition count after {} in {}", san, game.position().to_fen());
+    }
+    assert!(game.is_threefold_repetition());
+
+    // the ep square is kept when a pawn could take there, so the position after the double push is new
+    let mut game = Game::from_position(Position::from_fen("4k3/8/8/8/5p2/8/4P3/4K3 w - - 0 1").unwrap());
+    let mv = game.position().parse_san("e4").unwrap();
+    game.make_move(&mv).unwrap();
+    assert_eq!(game.position().ep_square, Some(Square::E3));
+    println!("Game passed");
+}
@@ -0,0 +1,29 @@
+pub mod types;
+pub mod bitboard;
+pub mod position;
+pub mod attacks;
+pub mod fen;
+pub mod validate;
+pub mod zobrist;
+pub mod termination;
+pub mod game;
+pub mod uci;
+pub mod san;
+pub mod pgn;
+pub mod move_list;
+pub mod movegen;
+pub mod perft;
+pub mod see;
+
+pub use types::{Bitboard, Color, Move, MoveKind, ParseSquareError, Piece, Square};
+pub use position::{Position, Undo};
+pub use fen::FenError;
+pub use validate::{PositionError, Violation};
+pub use termination::Termination;
+pub use game::{DrawReason, Game, GameResult};
+pub use uci::UciMoveError;
+pub use san::SanError;
+pub use pgn::{PgnGame, PgnNode, PgnReader, PgnError};
+pub use move_list::MoveList;
+pub use movegen::{gen_legal_moves, gen_captures, gen_quiets, gen_quiet_checks, gen_evasions};
+pub use perft::{perft, perft_unmake, perft_staged, perft_divide, perft_divide_hash, perft_stats, perft_hash, fast_perft, fast_perft_hash, PerftStats, PerftTable, PerftTableError};
@@ -1,64 +1,155 @@
-#![allow(dead_code)]
-
-mod types;
-mod bitboard;
-mod position;
-mod movegen;
-mod perft;
-
-use std::{collections::VecDeque, sync::{Arc, Mutex}, thread};
+use std::process::ExitCode;
+use std::time::Instant;
+
+use jchess::{Position, PerftTable, fast_perft, fast_perft_hash, perft_divide, perft_divide_hash};
+use jchess::fen::STARTING_FEN;
+use jchess::{attacks, game, perft, pgn, position, see};
+
+const USAGE: &str = "usage:
+    jchess perft [--fen <FEN>] --depth <N> [--threads <T>] [--divide] [--hash <MB>]
+    jchess verify
+
+perft counts the leaf nodes below a position, starting from the standard position unless a FEN is given. --threads
+defaults to the number of cores, --divide prints the count below each root move (on one thread) and --hash reuses
+counts of transposed positions from a table of the given size. verify runs every correctness check.";
+
+struct PerftOptions {
+    pos: Position,
+    depth: u8,
+    threads: u8,
+    divide: bool,
+    table: Option<PerftTable>
+}
 
-use position::Position;
-use types::*;
-use movegen::gen_legal_moves;
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        Some("perft") => match parse_perft_options(&args[1..]) {
+            Ok(options) => run_perft(&options),
+            Err(message) => usage_error(&message)
+        },
+        Some("verify") if args.len() == 1 => run_verify(),
+        Some("verify") => usage_error("verify takes no options"),
+        Some(command) => usage_error(&format!("unknown command '{}'", command)),
+        None => usage_error("missing command")
+    }
+}
 
-fn fast_perft(pos: Position, depth: u8, thread_count: u8) -> u64 {
-    let mut move_list: [Move; 256] = [Move::default(); 256];
-    let mut move_count = 0;
+fn usage_error(message: &str) -> ExitCode {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    ExitCode::from(2)
+}
 
-    gen_legal_moves(&pos, &mut move_list, &mut move_count);
+fn parse_perft_options(args: &[String]) -> Result<PerftOptions, String> {
+    let mut fen: &str = STARTING_FEN;
+    let mut depth: Option<u8> = None;
+    let mut threads: u8 = std::thread::available_parallelism().map_or(1, |cores| cores.get().min(u8::MAX as usize) as u8);
+    let mut divide: bool = false;
+    let mut hash_mb: Option<usize> = None;
+
+    let mut args = args.iter();
+    while let Some(option) = args.next() {
+        match option.as_str() {
+            "--fen" => fen = option_value(&mut args, option)?,
+            "--depth" => depth = Some(parse_option_value(&mut args, option)?),
+            "--threads" => threads = parse_option_value(&mut args, option)?,
+            "--divide" => divide = true,
+            "--hash" => hash_mb = Some(parse_option_value(&mut args, option)?),
+            _ => return Err(format!("unknown option '{}'", option))
+        }
+    }
 
-    let queue: Arc<Mutex<VecDeque<Move>>> = Arc::new(Mutex::new(move_list.iter().take(move_count).copied().collect()));
+    let depth: u8 = depth.ok_or("missing --depth")?;
+    if depth == 0 {
+        return Err("--depth must be at least 1".to_string());
+    }
+    if threads == 0 {
+        return Err("--threads must be at least 1".to_string());
+    }
+    if hash_mb == Some(0) {
+        return Err("--hash must be at least 1".to_string());
+    }
 
-    let mut handles = vec![];
+    let pos: Position = Position::from_fen_validated(fen).map_err(|err| err.to_string())?;
 
-    for _ in 0..thread_count {
-        let queue: Arc<Mutex<VecDeque<Move>>> = Arc::clone(&queue);
-        let handle = thread::spawn(move || {
-            let mut thread_sum: u64 = 0;
+    // allocate the table up front so a size that can't be had is reported like any other bad option
+    let table: Option<PerftTable> = hash_mb.map(PerftTable::new).transpose().map_err(|err| err.to_string())?;
 
-            loop {
-                let move_p = {
-                    let mut queue = queue.lock().unwrap();
-                    queue.pop_front()
-                };
+    Ok(PerftOptions { pos, depth, threads, divide, table })
+}
 
-                if let Some(mv) = move_p {
-                    let mut updated_pos = pos;
-                    updated_pos.make(&mv);
+fn option_value<'a>(args: &mut impl Iterator<Item = &'a String>, option: &str) -> Result<&'a str, String> {
+    args.next().map(String::as_str).ok_or(format!("missing value for {}", option))
+}
 
-                    thread_sum += perft::perft(&updated_pos, depth-1);
-                } else {
-                    break;
-                }
-            }
+fn parse_option_value<'a, T: std::str::FromStr>(args: &mut impl Iterator<Item = &'a String>, option: &str) -> Result<T, String> {
+    let value: &str = option_value(args, option)?;
+    value.parse().map_err(|_| format!("invalid value '{}' for {}", value, option))
+}
 
+fn run_perft(options: &PerftOptions) -> ExitCode {
+    let table: Option<&PerftTable> = options.table.as_ref();
+
+    let start = Instant::now();
+    let nodes: u64 = if options.divide {
+        let divide: Vec<(String, u64)> = match table {
+            Some(table) => perft_divide_hash(&options.pos, options.depth, table),
+            None => perft_divide(&options.pos, options.depth)
+        };
+        for (uci, nodes) in &divide {
+            println!("{}: {}", uci, nodes);
+        }
+        println!();
+        divide.iter().map(|(_, nodes)| nodes).sum()
+    } else {
+        match table {
+            Some(table) => fast_perft_hash(options.pos, options.depth, options.threads, table),
+            None => fast_perft(options.pos, options.depth, options.threads)
+        }
+    };
+    let elapsed: f64 = start.elapsed().as_secs_f64();
+
+    // same total line as the "go perft" output of other engines, so divide output can be diffed against theirs
+    println!("Nodes searched: {}", nodes);
+    println!("Time: {:.3}s", elapsed);
+    println!("NPS: {:.0}", nodes as f64 / elapsed.max(f64::EPSILON));
+    if let Some(table) = table {
+        println!("Hash: {} MB, {:.1}% of {} probes hit", table.size_bytes() / (1024 * 1024), table.hit_rate() * 100.0, table.probes());
+    }
 
-            thread_sum
-        });
+    ExitCode::SUCCESS
+}
 
-        handles.push(handle);
+// run every check, carrying on past failures so one run reports all of them
+fn run_verify() -> ExitCode {
+    let checks: [(&str, fn()); 8] = [
+        ("chess960", position::check_chess960_correctness),
+        ("game", game::check_game_correctness),
+        ("PGN", pgn::check_pgn_correctness),
+        ("SEE", see::check_see_correctness),
+        ("attacks", attacks::check_attacks_correctness),
+        ("perft stats", perft::check_perft_stats),
+        ("hashed perft", perft::check_perft_hash),
+        ("movegen", perft::check_movegen_correctness)
+    ];
+
+    let mut failed: Vec<&str> = Vec::new();
+    for (name, check) in checks {
+        let start = Instant::now();
+        if std::panic::catch_unwind(check).is_ok() {
+            println!("{} check passed in {:.2?}", name, start.elapsed());
+        } else {
+            eprintln!("{} check failed", name);
+            failed.push(name);
+        }
     }
 
-    let mut perft_result = 0;
-    for handle in handles{
-        perft_result += handle.join().unwrap();
+    if failed.is_empty() {
+        println!("All checks passed");
+        ExitCode::SUCCESS
+    } else {
+        eprintln!("Failed checks: {}", failed.join(", "));
+        ExitCode::FAILURE
     }
-
-    perft_result
 }
-
-fn main() {
-    let p = Position::new();
-    println!("{}", fast_perft(p, 6, 8));
-}
\ No newline at end of file
@@ -4,338 +4,395 @@ mod magics;
 use crate::types::*;
 use crate::bitboard::*;
 
-use crate::position::Position;
+use crate::move_list::{self, MoveList};
+use crate::position::{Position, ep_capture_square};
 use attack_tables::ATTACK_TABLE;
 
-pub fn gen_legal_moves(pos: &Position, move_list: &mut [Move; 256], move_count: &mut usize) {
-    assert_eq!(*move_count, 0);
+// which moves a generator produces. captures include en passant and every promotion, quiets include castling
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum GenType {
+    Captures,
+    Quiets,
+    All
+}
+
+pub fn gen_legal_moves(pos: &Position, move_list: &mut MoveList) {
+    gen_staged_legal_moves(pos, move_list, GenType::All);
+}
+
+// legal captures, including en passant, and promotions whether they capture or not
+pub fn gen_captures(pos: &Position, move_list: &mut MoveList) {
+    gen_staged_legal_moves(pos, move_list, GenType::Captures);
+}
+
+// legal moves that gen_captures leaves out: non-capturing, non-promoting moves and castling
+pub fn gen_quiets(pos: &Position, move_list: &mut MoveList) {
+    gen_staged_legal_moves(pos, move_list, GenType::Quiets);
+}
+
+// the quiet moves that give check
+pub fn gen_quiet_checks(pos: &Position, move_list: &mut MoveList) {
+    gen_quiets(pos, move_list);
+    move_list.retain(|mv| pos.gives_check(mv));
+}
+
+// legal moves when the side to move is in check. gives the same moves as gen_legal_moves, which it falls back to when
+// the side to move isn't in check, since evasion generation needs a checker to work from
+pub fn gen_evasions(pos: &Position, move_list: &mut MoveList) {
+    gen_staged_legal_moves(pos, move_list, GenType::All);
+}
+
+fn gen_staged_legal_moves(pos: &Position, move_list: &mut MoveList, gen_type: GenType) {
+    move_list.clear();
 
-    if is_check(pos) {
-        gen_evasions(pos, move_list, move_count);
+    if pos.is_check() {
+        gen_legal_evasions(pos, move_list, gen_type);
     } else {
-        gen_masked_pseudo_legal_moves(pos, move_list, move_count, BB_ALL, BB_ALL);
+        gen_masked_legal_moves(pos, move_list, BB_ALL, BB_ALL, gen_type);
     }
-    // iterate over pseudo legal moves, getting rid of illegal moves
-    let mut i: usize = 0;
-    let mut j: usize = 0;
-    while j < *move_count {
-        if is_legal(pos, &move_list[j]) {
-            move_list[i] = move_list[j];
-            i += 1;
+}
+
+impl Position {
+    pub fn legal_moves(&self) -> MoveList {
+        let mut move_list = MoveList::new();
+        gen_legal_moves(self, &mut move_list);
+        move_list
+    }
+
+    // legal moves by value, e.g. for chaining iterator adapters
+    pub fn legal_moves_iter(&self) -> move_list::IntoIter {
+        self.legal_moves().into_iter()
+    }
+
+    // recompute checkers, pinned and pin_rays for the side to move. make, unmake and the constructors keep them up to date
+    pub(crate) fn update_check_info(&mut self) {
+        self.checkers = BB_NONE;
+        self.pinned = BB_NONE;
+        self.pin_rays = BB_NONE;
+
+        // positions without a king can still be parsed, they just can't be played from
+        let own: Bitboard = self.occupied[self.turn.index()];
+        let opp: Bitboard = self.occupied[(!self.turn).index()];
+        if self.kings & own == 0 {
+            return;
+        }
+        let king: Square = lsb(self.kings & own);
+
+        self.checkers = opp & (
+            (ATTACK_TABLE.get_pawn_attacks(king, self.turn) & self.pawns) |
+            (ATTACK_TABLE.get_jump_attacks(king, Piece::Knight) & self.knights)
+        );
+
+        // enemy sliders aiming at the king give check if nothing is in between, and pin the piece in between if
+        // there is exactly one of ours
+        let occupied: Bitboard = own | opp;
+        let mut snipers: Bitboard = opp & (
+            (ATTACK_TABLE.get_sliding_attacks(king, Piece::Rook, BB_NONE) & (self.rooks | self.queens)) |
+            (ATTACK_TABLE.get_sliding_attacks(king, Piece::Bishop, BB_NONE) & (self.bishops | self.queens))
+        );
+        while snipers > 0 {
+            let sniper: Square = pop_lsb(&mut snipers);
+            let line: Bitboard = ATTACK_TABLE.get_line(sniper, king) & !square_bb(king);
+            let blockers: Bitboard = line & occupied & !square_bb(sniper);
+
+            if blockers == 0 {
+                self.checkers |= square_bb(sniper);
+            } else if blockers & (blockers - 1) == 0 && blockers & own > 0 {
+                self.pinned |= blockers;
+                self.pin_rays |= line;
+            }
+        }
+    }
+
+    // whether a legal move puts the opponent in check, without making it. covers direct checks by the moved or
+    // promoted piece, discovered checks (including through the pawn captured en passant) and the rook after castling
+    pub fn gives_check(&self, mv: &Move) -> bool {
+        let them: Color = !self.turn;
+        let their_king: Bitboard = self.kings & self.occupied[them.index()];
+        if their_king == 0 {
+            return false;
         }
-        j += 1;
+        let king: Square = lsb(their_king);
+
+        let from_square: Square = mv.from_square();
+        let to_square: Square = mv.to_square();
+
+        // squares the move empties and fills, and the piece that could give check directly and where it lands
+        let (vacated, filled, checker, checker_square): (Bitboard, Bitboard, Option<Piece>, Square) = match mv.kind() {
+            MoveKind::Normal => (square_bb(from_square), square_bb(to_square), self.piece_at(from_square), to_square),
+            MoveKind::Promotion => (square_bb(from_square), square_bb(to_square), mv.promotion(), to_square),
+            MoveKind::EnPassant => {
+                let captured_pawn: Square = ep_capture_square(to_square, self.turn);
+                (square_bb(from_square) | square_bb(captured_pawn), square_bb(to_square), Some(Piece::Pawn), to_square)
+            }
+            MoveKind::Castle => {
+                let (king_to, rook_to): (Square, Square) = castling_targets(from_square, to_square);
+                (square_bb(from_square) | square_bb(to_square), square_bb(king_to) | square_bb(rook_to), Some(Piece::Rook), rook_to)
+            }
+        };
+        let occupied: Bitboard = (self.occupied[Color::White.index()] | self.occupied[Color::Black.index()]) & !vacated | filled;
+
+        // direct check. attacks are looked up from the king, which is the same as from the checker for every piece
+        let direct: Bitboard = match checker {
+            Some(Piece::Pawn) => ATTACK_TABLE.get_pawn_attacks(king, them),
+            Some(pt @ Piece::Knight) => ATTACK_TABLE.get_jump_attacks(king, pt),
+            Some(pt @ (Piece::Bishop | Piece::Rook | Piece::Queen)) => ATTACK_TABLE.get_sliding_attacks(king, pt, occupied),
+            _ => BB_NONE
+        };
+        if direct & square_bb(checker_square) > 0 {
+            return true;
+        }
+
+        // discovered check. only possible if the move empties a square on a line to the king
+        if vacated & ATTACK_TABLE.get_sliding_attacks(king, Piece::Queen, BB_NONE) == 0 {
+            return false;
+        }
+        let sliders: Bitboard = self.occupied[self.turn.index()] & !vacated;
+        let discovered: Bitboard = (ATTACK_TABLE.get_sliding_attacks(king, Piece::Rook, occupied) & (self.rooks | self.queens)) |
+            (ATTACK_TABLE.get_sliding_attacks(king, Piece::Bishop, occupied) & (self.bishops | self.queens));
+
+        discovered & sliders > 0
+    }
+}
+
+// squares pieces other than pawns may move to for a type of generation
+#[inline]
+fn gen_type_targets(pos: &Position, gen_type: GenType) -> Bitboard {
+    let occupied: Bitboard = pos.occupied[Color::White.index()] | pos.occupied[Color::Black.index()];
+    match gen_type {
+        GenType::Captures => pos.occupied[(!pos.turn).index()],
+        GenType::Quiets => !occupied,
+        GenType::All => BB_ALL
+    }
+}
+
+// squares a piece on sq can move to without exposing its king: anywhere if it isn't pinned, otherwise along the pin
+#[inline]
+fn pin_mask(pos: &Position, king: Square, sq: Square) -> Bitboard {
+    if pos.pinned & square_bb(sq) > 0 {
+        pos.pin_rays & ATTACK_TABLE.get_ray(king, sq)
+    } else {
+        BB_ALL
     }
-    *move_count = i;
 }
 
-// generate all pseudo-legal moves, not caring about issues with checks, etc.
-fn gen_masked_pseudo_legal_moves(pos: &Position, move_list: &mut [Move; 256], move_count: &mut usize, from_mask: Bitboard, to_mask: Bitboard) {      
-    let occupied: Bitboard = pos.occupied[color::WHITE] | pos.occupied[color::BLACK];
-    let self_occupied: Bitboard = pos.occupied[pos.turn];
-    let opponent_occupied: Bitboard = pos.occupied[pos.turn ^ 1];
+// generate all legal moves of a type for pieces on from_mask going to to_mask. king moves and castling are only
+// generated if the king is on from_mask, and to_mask doesn't restrict castling to anything but the rook's square
+fn gen_masked_legal_moves(pos: &Position, move_list: &mut MoveList, from_mask: Bitboard, to_mask: Bitboard, gen_type: GenType) {
+    let occupied: Bitboard = pos.occupied[Color::White.index()] | pos.occupied[Color::Black.index()];
+    let self_occupied: Bitboard = pos.occupied[pos.turn.index()];
+    let opponent_occupied: Bitboard = pos.occupied[(!pos.turn).index()];
+    let king: Square = lsb(pos.kings & self_occupied);
+
+    let captures: bool = gen_type != GenType::Quiets;
+    let quiets: bool = gen_type != GenType::Captures;
 
     let pawns: Bitboard = self_occupied & pos.pawns & from_mask;
 
     // generate pawn attacks
-    let mut pawn_bb: Bitboard = pawns;
+    let mut pawn_bb: Bitboard = if captures {pawns} else {BB_NONE};
     while pawn_bb > 0 {
         let from_square: Square = pop_lsb(&mut pawn_bb);
 
-        let mut attacks: Bitboard = attacks_from_square(pos, from_square) & opponent_occupied & to_mask;
+        let mut attacks: Bitboard = ATTACK_TABLE.get_pawn_attacks(from_square, pos.turn) & opponent_occupied & to_mask & pin_mask(pos, king, from_square);
         while attacks > 0 {
             let to_square: Square = pop_lsb(&mut attacks);
             // if promotion
-            if square_rank(to_square) == 0 || square_rank(to_square) == 7 {
-                move_list[*move_count] = Move {from_square, to_square, promotion: piece::QUEEN};
-                *move_count += 1;
-
-                move_list[*move_count] = Move {from_square, to_square, promotion: piece::ROOK};
-                *move_count += 1;
-
-                move_list[*move_count] = Move {from_square, to_square, promotion: piece::BISHOP};
-                *move_count += 1;
-
-                move_list[*move_count] = Move {from_square, to_square, promotion: piece::KNIGHT};
-                *move_count += 1;
+            if to_square.rank() == 0 || to_square.rank() == 7 {
+                for promotion in Piece::PROMOTIONS {
+                    move_list.push(Move::new_promotion(from_square, to_square, promotion));
+                }
             } else {
-                move_list[*move_count] = Move {from_square, to_square, promotion: piece::NONE};
-                *move_count += 1;
+                move_list.push(Move::new(from_square, to_square));
             }
         }
     }
 
-    let pieces: Bitboard = self_occupied & !pawns & from_mask;
+    let targets: Bitboard = gen_type_targets(pos, gen_type);
 
     // generate piece attacks
-    let mut piece_bb: Bitboard = pieces;
-    while piece_bb > 0 {
-        let from_square: Square = pop_lsb(&mut piece_bb);
+    for (pt, pieces) in [(Piece::Knight, pos.knights), (Piece::Bishop, pos.bishops), (Piece::Rook, pos.rooks), (Piece::Queen, pos.queens)] {
+        let mut piece_bb: Bitboard = pieces & self_occupied & from_mask;
+        while piece_bb > 0 {
+            let from_square: Square = pop_lsb(&mut piece_bb);
+
+            let mut attacks: Bitboard = attacks_mask(pos, from_square, pt, pos.turn) & to_mask & targets & pin_mask(pos, king, from_square);
+            while attacks > 0 {
+                let to_square: Square = pop_lsb(&mut attacks);
+                move_list.push(Move::new(from_square, to_square));
+            }
+        }
+    }
 
-        let mut attacks: Bitboard = attacks_from_square(pos, from_square) & to_mask;
-        while attacks > 0 {
-            let to_square: Square = pop_lsb(&mut attacks);
-            move_list[*move_count] = Move {from_square, to_square, promotion: piece::NONE};
-            *move_count += 1;
+    if pos.kings & self_occupied & from_mask > 0 {
+        // generate king moves. the king is taken off the board so it can't hide behind itself from a slider
+        let without_king: Bitboard = occupied & !square_bb(king);
+        let mut king_moves: Bitboard = attacks_mask(pos, king, Piece::King, pos.turn) & to_mask & targets;
+        while king_moves > 0 {
+            let to_square: Square = pop_lsb(&mut king_moves);
+            if !is_attacked_with_occupancy(pos, to_square, without_king) {
+                move_list.push(Move::new(king, to_square));
+            }
         }
 
-    }
+        // generate castling moves, encoded as the king capturing its own rook so chess960 castling is unambiguous
+        let backrank: Bitboard = if pos.turn == Color::White {BB_RANK_1} else {BB_RANK_8};
+        if quiets && pos.checkers == 0 && square_bb(king) & backrank > 0 {
+            let mut castling_rooks: Bitboard = pos.castling_rights & backrank & self_occupied & pos.rooks & to_mask;
+            while castling_rooks > 0 {
+                let rook: Square = pop_lsb(&mut castling_rooks);
+                let (king_to, rook_to): (Square, Square) = castling_targets(king, rook);
+
+                // everything between the king, the rook and their targets has to be empty, apart from the king and rook themselves
+                let without_castlers: Bitboard = occupied & !square_bb(king) & !square_bb(rook);
+                let king_path: Bitboard = ATTACK_TABLE.get_line(king, king_to);
+                if (king_path | ATTACK_TABLE.get_line(rook, rook_to)) & without_castlers > 0 {
+                    continue;
+                }
 
-    // generate castling moves
-    if self_occupied & pos.kings & from_mask > 0 {
-        let king: Square = lsb(self_occupied & pos.kings & from_mask);
-        let backrank: Bitboard = if pos.turn == color::WHITE {BB_RANK_1} else {BB_RANK_8};
-        
-        let mut castling_squares: Bitboard = pos.castling_rights & backrank;
-        while castling_squares > 0 {
-            let candidate_square: Square = pop_lsb(&mut castling_squares);
-
-            if king < candidate_square && CASTLE_BLOCKER_MASK_KINGSIDE & backrank & occupied == 0 && square_bb(king+2) & to_mask > 0 {
-                // kingside castling
-                if !is_attacked(pos, lsb(BB_FILE_E & backrank)) &&
-                !is_attacked(pos, lsb(BB_FILE_F & backrank)) &&
-                !is_attacked(pos, lsb(BB_FILE_G & backrank)) {
-                    move_list[*move_count] = Move {
-                        from_square: king,
-                        to_square: king + 2,
-                        promotion: piece::NONE
-                    };
-                    *move_count += 1;
+                // the king can't pass through or land on an attacked square. the castling rook is taken off the board
+                // since it may be shielding the king's target from a slider on the back rank
+                let mut path: Bitboard = king_path;
+                let mut safe: bool = true;
+                while path > 0 && safe {
+                    safe = !is_attacked_with_occupancy(pos, pop_lsb(&mut path), without_castlers);
                 }
-            } else if king > candidate_square && CASTLE_BLOCKER_MASK_QUEENSIDE & backrank & occupied == 0 && square_bb(king-2) & to_mask > 0 {
-                // queenside castling
-                if !is_attacked(pos, lsb(BB_FILE_C & backrank)) &&
-                !is_attacked(pos, lsb(BB_FILE_D & backrank)) &&
-                !is_attacked(pos, lsb(BB_FILE_E & backrank)) {
-                    move_list[*move_count] = Move {
-                        from_square: king,
-                        to_square: king - 2,
-                        promotion: piece::NONE
-                    };
-                    *move_count += 1;
+
+                if safe {
+                    move_list.push(Move::new_castle(king, rook));
                 }
             }
         }
     }
 
-    // prepare pawn advance generation
+    // prepare pawn advance generation. pinned pawns can only advance when pinned along the king's file
+    let pushers: Bitboard = pawns & (!pos.pinned | file_bb(king));
     let mut single_advances: Bitboard;
     let mut double_advances: Bitboard;
     let single_delta: i8;
-    if pos.turn == color::WHITE {
-        single_advances = (pawns << 8) & !occupied;
+    if pos.turn == Color::White {
+        single_advances = (pushers << 8) & !occupied;
         double_advances = (single_advances << 8) & !occupied & BB_RANK_4 & to_mask;
         single_delta = 8;
     } else {
-        single_advances = (pawns >> 8) & !occupied;
+        single_advances = (pushers >> 8) & !occupied;
         double_advances = (single_advances >> 8) & !occupied & BB_RANK_5 & to_mask;
         single_delta = -8;
     }
 
-    single_advances &= to_mask;
+    // promotions count as captures, so they come first in a search
+    let promotion_ranks: Bitboard = BB_RANK_1 | BB_RANK_8;
+    single_advances &= to_mask & match gen_type {
+        GenType::Captures => promotion_ranks,
+        GenType::Quiets => !promotion_ranks,
+        GenType::All => BB_ALL
+    };
+    if !quiets {
+        double_advances = BB_NONE;
+    }
 
     // generate single pawn moves
     while single_advances > 0 {
         let to_square: Square = pop_lsb(&mut single_advances);
-        let from_square: Square = (to_square as i8 - single_delta) as usize;
-        
-        // if promotion
-        if square_rank(to_square) == 0 || square_rank(to_square) == 7 {
-            move_list[*move_count] = Move {from_square, to_square, promotion: piece::QUEEN};
-            *move_count += 1;
-
-            move_list[*move_count] = Move {from_square, to_square, promotion: piece::ROOK};
-            *move_count += 1;
+        let from_square: Square = to_square.offset_unchecked(-single_delta);
 
-            move_list[*move_count] = Move {from_square, to_square, promotion: piece::BISHOP};
-            *move_count += 1;
-
-            move_list[*move_count] = Move {from_square, to_square, promotion: piece::KNIGHT};
-            *move_count += 1;
+        // if promotion
+        if to_square.rank() == 0 || to_square.rank() == 7 {
+            for promotion in Piece::PROMOTIONS {
+                move_list.push(Move::new_promotion(from_square, to_square, promotion));
+            }
         } else {
-            move_list[*move_count] = Move {from_square, to_square, promotion: piece::NONE};
-            *move_count += 1;
+            move_list.push(Move::new(from_square, to_square));
         }
     }
 
     // generate double pawn moves
     while double_advances > 0 {
         let to_square: Square = pop_lsb(&mut double_advances);
-        let from_square: Square = (to_square as i8 - single_delta*2) as usize;
+        let from_square: Square = to_square.offset_unchecked(-single_delta*2);
 
-        move_list[*move_count] = Move {from_square, to_square, promotion: piece::NONE};
-        *move_count += 1;
+        move_list.push(Move::new(from_square, to_square));
     }
 
-    // generate en passant
-    if pos.ep_square != square::NONE && square_bb(pos.ep_square) & to_mask > 0 {
-        let mut capturers = pawns & self_occupied & ATTACK_TABLE.get_pawn_attacks(pos.ep_square, pos.turn ^ 1) & from_mask;
+    // generate en passant. taking two pawns off a rank at once can expose the king in ways pins don't cover,
+    // so each capture is checked on its own
+    if let Some(ep_square) = pos.ep_square.filter(|&ep_square| captures && square_bb(ep_square) & to_mask > 0) {
+        let mut capturers = pawns & ATTACK_TABLE.get_pawn_attacks(ep_square, !pos.turn);
         while capturers > 0 {
             let from_square = pop_lsb(&mut capturers);
 
-            move_list[*move_count] = Move {from_square, to_square: pos.ep_square, promotion: piece::NONE};
-            *move_count += 1;
+            if is_legal_en_passant(pos, king, from_square, ep_square) {
+                move_list.push(Move::new_en_passant(from_square, ep_square));
+            }
         }
     }
 }
 
-// generate moves that get the king out of check.
-fn gen_evasions(pos: &Position, move_list: &mut [Move; 256], move_count: &mut usize) {
-    let king: Square = lsb(pos.kings & pos.occupied[pos.turn]);
-    let checkers: Bitboard = attackers_mask(pos, king, pos.turn);
-
-    // generate attack rays from the sliding pieces towards the king
-    let mut sliders: Bitboard = checkers & (pos.rooks | pos.bishops | pos.queens);
-    let mut attack_mask: Bitboard = BB_NONE;
-    while sliders > 0 {
-        let slider_square = pop_lsb(&mut sliders);
-        attack_mask |= ATTACK_TABLE.get_ray(slider_square, king) & !square_bb(slider_square);
-    }
-    
+// generate legal moves of a type that get the king out of check
+fn gen_legal_evasions(pos: &Position, move_list: &mut MoveList, gen_type: GenType) {
+    let king: Square = lsb(pos.kings & pos.occupied[pos.turn.index()]);
+
     // generate king moves
-    let mut king_moves: Bitboard = attacks_from_square(pos, king) & !attack_mask;
-    while king_moves > 0 {
-        let king_move: Square = pop_lsb(&mut king_moves);
-        if !is_attacked(pos, king_move) {
-            move_list[*move_count] = Move {from_square: king, to_square: king_move, promotion: piece::NONE};
-            *move_count += 1;
-        }
-    }
+    gen_masked_legal_moves(pos, move_list, square_bb(king), BB_ALL, gen_type);
 
     // if king is in double check, skip generating other moves
-    if popcount(checkers) > 1 {
+    if popcount(pos.checkers) > 1 {
         return
     }
 
-    let checker_square: Square = lsb(checkers);
-    let checker_type: Piece = pos.piece_at(checker_square);
+    let checker_square: Square = lsb(pos.checkers);
+    let checker_type: Option<Piece> = pos.piece_at(checker_square);
 
-    let blocking_mask: Bitboard;
-    match checker_type {
-        piece::BISHOP | piece::ROOK | piece::QUEEN => {
+    let blocking_mask: Bitboard = match checker_type {
+        Some(Piece::Bishop | Piece::Rook | Piece::Queen) => {
             // if checked by bishop/rook/queen, generate moves that capture or block
-            blocking_mask = ATTACK_TABLE.get_line(checker_square, king) & !pos.kings;
+            ATTACK_TABLE.get_line(checker_square, king) & !pos.kings
         }
-        piece::PAWN => {
+        Some(Piece::Pawn) => {
             // handle case where checking pawn can be en passanted
-            if pos.ep_square != square::NONE {
-                gen_masked_pseudo_legal_moves(pos, move_list, move_count, BB_ALL & pos.pawns, square_bb(pos.ep_square));
-                blocking_mask = checkers;
-            } else {
-                blocking_mask = checkers;
+            if let Some(ep_square) = pos.ep_square {
+                gen_masked_legal_moves(pos, move_list, pos.pawns, square_bb(ep_square), gen_type);
             }
-            
-        }
-        piece::KNIGHT => {
-            blocking_mask = checkers;
-            
+            pos.checkers
         }
+        Some(Piece::Knight) => pos.checkers,
         _ => panic!("Something other than a piece is checking your king :(")
-    }
+    };
 
-    gen_masked_pseudo_legal_moves(pos, move_list, move_count, BB_ALL & !pos.kings, blocking_mask);
+    gen_masked_legal_moves(pos, move_list, !pos.kings, blocking_mask, gen_type);
 }
 
 // generates all possible attacks from a square, excluding self captures and en passant
 #[inline]
 fn attacks_mask(pos: &Position, sq: Square, pt: Piece, co: Color) -> Bitboard {
     (match pt {
-        piece::PAWN => ATTACK_TABLE.get_pawn_attacks(sq, co),
-        piece::KNIGHT | piece::KING => ATTACK_TABLE.get_jump_attacks(sq, pt),
-        piece::ROOK | piece::BISHOP | piece::QUEEN => ATTACK_TABLE.get_sliding_attacks(sq, pt, pos.occupied[color::WHITE] | pos.occupied[color::BLACK]),
-        piece::NONE => BB_NONE,
-        _ => panic!()
-    }) & !pos.occupied[co]
-}
-
-// returns attack mask for a given square using piece and color at that square
-#[inline]
-fn attacks_from_square(pos: &Position, sq: Square) -> Bitboard {
-    attacks_mask(pos, sq, pos.piece_at(sq), pos.color_at(sq))
+        Piece::Pawn => ATTACK_TABLE.get_pawn_attacks(sq, co),
+        Piece::Knight | Piece::King => ATTACK_TABLE.get_jump_attacks(sq, pt),
+        Piece::Rook | Piece::Bishop | Piece::Queen => ATTACK_TABLE.get_sliding_attacks(sq, pt, pos.occupied[Color::White.index()] | pos.occupied[Color::Black.index()])
+    }) & !pos.occupied[co.index()]
 }
 
+// whether the side not to move attacks a square, with sliders seeing through the given occupancy instead of the board's
 #[inline]
-fn is_attacked(pos: &Position, sq: Square) -> bool {
-    attackers_mask(pos, sq, pos.turn) > 0
+fn is_attacked_with_occupancy(pos: &Position, sq: Square, occupied: Bitboard) -> bool {
+    pos.attackers_to(sq, !pos.turn, occupied) > 0
 }
 
-// returns a mask of all pieces attacking a square. co is the color of the side being attacked. Excluding en passant
-#[inline]
-fn attackers_mask(pos: &Position, sq: Square, co: Color) -> Bitboard {
-    let mut attackers = BB_NONE;
-
-    attackers |= pos.pawns & attacks_mask(pos, sq, piece::PAWN, co);
-    attackers |= pos.knights & attacks_mask(pos, sq, piece::KNIGHT, co);
-    attackers |= pos.bishops & attacks_mask(pos, sq, piece::BISHOP, co);
-    attackers |= pos.rooks & attacks_mask(pos, sq, piece::ROOK, co);
-    attackers |= pos.queens & attacks_mask(pos, sq, piece::QUEEN, co);
-    attackers |= pos.kings & attacks_mask(pos, sq, piece::KING, co);
-
-    attackers
-}
+// whether capturing en passant leaves the king safe. the capturing and captured pawns both leave the board, which
+// can open up a rank or diagonal to the king
+fn is_legal_en_passant(pos: &Position, king: Square, from_square: Square, ep_square: Square) -> bool {
+    let occupied: Bitboard = pos.occupied[Color::White.index()] | pos.occupied[Color::Black.index()];
+    let opp_occupied: Bitboard = pos.occupied[(!pos.turn).index()];
+    let captured_pawn: Square = ep_capture_square(ep_square, pos.turn);
 
-#[inline]
-fn is_check(pos: &Position) -> bool {
-    let king: Square = lsb(pos.kings & pos.occupied[pos.turn]);
-    is_attacked(pos, king)
-}
+    // simulate making EP move
+    let ep_board: Bitboard = occupied & !square_bb(captured_pawn) & !square_bb(from_square) | square_bb(ep_square);
 
-fn is_legal(pos: &Position, mv: &Move) -> bool {
-    // king can't move into check
-    if pos.piece_at(mv.from_square) == piece::KING && is_attacked(pos, mv.to_square){
+    // horizontal and vertical attacks
+    if ATTACK_TABLE.get_sliding_attacks(king, Piece::Rook, ep_board) & opp_occupied & (pos.rooks | pos.queens) > 0 {
         return false;
     }
 
-    let occupied: Bitboard = pos.occupied[color::WHITE] | pos.occupied[color::BLACK];
-    let self_occupied: Bitboard = pos.occupied[pos.turn];
-    let opp_occupied: Bitboard = pos.occupied[pos.turn^1];
-    
-
-    // handle rook pins (non-ep). to do this, pretend piece is slider and see if it can attack its own king and enemy slider of the same type
-    // (en passant trivial pins covered)
-    let rook_attacks: Bitboard = ATTACK_TABLE.get_sliding_attacks(mv.from_square, piece::ROOK, occupied);
-    
-    // we need to handle vertical and horizontal seperately
-    let vertical_attacks: Bitboard = rook_attacks & file_bb(mv.from_square);
-    if vertical_attacks & self_occupied & pos.kings > 0 && vertical_attacks & opp_occupied & (pos.rooks | pos.queens) > 0 {
-        // if to_square is not between the attacker and king, move is illegal
-        return square_bb(mv.to_square) & vertical_attacks > 0;
-    }
-
-    let horizontal_attacks: Bitboard = rook_attacks & rank_bb(mv.from_square);
-    if horizontal_attacks & self_occupied & pos.kings > 0 && horizontal_attacks & opp_occupied & (pos.rooks | pos.queens) > 0 {
-        return square_bb(mv.to_square) & horizontal_attacks > 0;
-    }
-
-    // bishop pins (en passant trivial pins covered)
-    let bishop_attacks: Bitboard = ATTACK_TABLE.get_sliding_attacks(mv.from_square, piece::BISHOP, occupied);
-
-    let asc_attacks: Bitboard = bishop_attacks & diag_asc_bb(mv.from_square);
-    if asc_attacks & self_occupied & pos.kings > 0 && asc_attacks & opp_occupied & (pos.bishops | pos.queens) > 0 {
-        return square_bb(mv.to_square) & asc_attacks > 0;
-    }
-
-    let desc_attacks: Bitboard = bishop_attacks & diag_desc_bb(mv.from_square);
-    if desc_attacks & self_occupied & pos.kings > 0 && desc_attacks & opp_occupied & (pos.bishops | pos.queens) > 0 {
-        return square_bb(mv.to_square) & desc_attacks > 0;
-    }
-
-    // en-passant non-trivial pins
-    if pos.piece_at(mv.from_square) == piece::PAWN && pos.piece_at(mv.to_square) == piece::NONE && square_file(mv.from_square) != square_file(mv.to_square) {
-        let captured_pawn: Square = (pos.ep_square as i8 + if pos.turn == color::WHITE {-8} else {8}) as Square;
-
-        // simulate making EP move
-        let ep_board: Bitboard = occupied & !square_bb(captured_pawn) & !square_bb(mv.from_square) | square_bb(pos.ep_square);
-
-        // horizontal attacks
-        if ATTACK_TABLE.get_sliding_attacks(lsb(pos.kings & self_occupied), piece::ROOK, ep_board) & opp_occupied & (pos.rooks | pos.queens) > 0 {
-            return false;
-        }
-
-        // diagonal attacks
-        if ATTACK_TABLE.get_sliding_attacks(lsb(pos.kings & self_occupied), piece::BISHOP, ep_board) & opp_occupied & (pos.bishops | pos.queens) > 0 {
-            return false;
-        }
-    }
-
-    true
-}
\ No newline at end of file
+    // diagonal attacks
+    ATTACK_TABLE.get_sliding_attacks(king, Piece::Bishop, ep_board) & opp_occupied & (pos.bishops | pos.queens) == 0
+}
@@ -58,19 +58,25 @@ impl AttackTable {
     }
 }
 
+impl Default for AttackTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // implement sliding attack lookups
 impl AttackTable {
     #[inline]
     pub fn get_pawn_attacks(&self, sq: Square, co: Color) -> Bitboard {
-        self.pawns[co][sq]
+        self.pawns[co.index()][sq.index()]
     }
 
     // get attacks for knights & kings
     #[inline]
     pub fn get_jump_attacks(&self, sq: Square, pt: Piece) -> Bitboard {
         match pt {
-            piece::KNIGHT => self.knights[sq],
-            piece::KING => self.kings[sq],
+            Piece::Knight => self.knights[sq.index()],
+            Piece::King => self.kings[sq.index()],
             _ => panic!("Incorrect piece type. This function is for knight & king attacks only")
         }
     }
@@ -79,9 +85,9 @@ impl AttackTable {
     #[inline]
     pub fn get_sliding_attacks(&self, sq: Square, pt: Piece, occupied: Bitboard) -> Bitboard {
         match pt {
-            piece::ROOK => self.get_rook_attacks(sq, occupied),
-            piece::BISHOP => self.get_bishop_attacks(sq, occupied),
-            piece::QUEEN => self.get_rook_attacks(sq, occupied) | self.get_bishop_attacks(sq, occupied),
+            Piece::Rook => self.get_rook_attacks(sq, occupied),
+            Piece::Bishop => self.get_bishop_attacks(sq, occupied),
+            Piece::Queen => self.get_rook_attacks(sq, occupied) | self.get_bishop_attacks(sq, occupied),
             _ => panic!("Incorrect piece type. This function is for sliding pieces only")
         }
     }
@@ -89,7 +95,7 @@ impl AttackTable {
     // return ray between two squares
     #[inline]
     pub fn get_ray(&self, sq1: Square, sq2: Square) -> Bitboard {
-        self.rays[sq1][sq2]
+        self.rays[sq1.index()][sq2.index()]
     }
 
     // return line between two endpoints inclusive
@@ -102,7 +108,7 @@ impl AttackTable {
 
     #[inline]
     fn get_rook_attacks(&self, sq: Square, occupied: Bitboard) -> Bitboard{
-        let m = self.rook_magics[sq];
+        let m = self.rook_magics[sq.index()];
         let index = (m.mask & occupied).wrapping_mul(m.magic) >> m.shift;
 
         self.rooks[index as usize + m.offset as usize]
@@ -110,7 +116,7 @@ impl AttackTable {
 
     #[inline]
     fn get_bishop_attacks(&self, sq: Square, occupied: Bitboard) -> Bitboard{
-        let m = self.bishop_magics[sq];
+        let m = self.bishop_magics[sq.index()];
         let index = (m.mask & occupied).wrapping_mul(m.magic) >> m.shift;
 
         self.bishops[index as usize + m.offset as usize]
@@ -122,37 +128,33 @@ impl AttackTable {
     fn init_pawns(&mut self) {
         let deltas: [[i8; 2]; 2] = [[7, 9], [-7, -9]];
 
-        let color_index = color::WHITE;
-        for sq in 0..64 {
-            self.pawns[color_index][sq] = step_mask(sq, &deltas[color_index]);
-        }
-
-        let color_index: usize = color::BLACK;
-        for sq in 0..64 {
-            self.pawns[color_index][sq] = step_mask(sq, &deltas[color_index]);
+        for co in Color::ALL {
+            for sq in Square::iter() {
+                self.pawns[co.index()][sq.index()] = step_mask(sq, &deltas[co.index()]);
+            }
         }
     }
 
     fn init_knights(&mut self) {
         let deltas = [17, 15, 10, 6, -17, -15, -10, -6];
 
-        for sq in 0..64 {
-            self.knights[sq as usize] = step_mask(sq, &deltas);
+        for sq in Square::iter() {
+            self.knights[sq.index()] = step_mask(sq, &deltas);
         }
     }
 
     fn init_kings(&mut self) {
         let deltas = [9, 8, 7, 1, -9, -8, -7, -1];
 
-        for sq in 0..64 {
-            self.kings[sq] = step_mask(sq, &deltas);
+        for sq in Square::iter() {
+            self.kings[sq.index()] = step_mask(sq, &deltas);
         }
     }
 
     fn init_rooks(&mut self) {
         let deltas = [-1, 1, -8, 8];
         let mut offset: usize = 0;
-        for sq in 0..64 {
+        for (sq, &magic) in Square::iter().zip(ROOK_MAGICS.iter()) {
             // generate the mask
             let edge_mask: Bitboard = ((BB_RANK_1 | BB_RANK_8) & !rank_bb(sq)) | (BB_FILE_A | BB_FILE_H) & !file_bb(sq);
             let mask: Bitboard = slider_mask(sq, &deltas, BB_NONE) & !edge_mask;
@@ -163,7 +165,7 @@ impl AttackTable {
             // iterate over every subset of the mask and set the attack mask in the lookup table
             let mut occupied: Bitboard = BB_NONE;
             loop {
-                let index: usize  = ((occupied.wrapping_mul(ROOK_MAGICS[sq])) >> shift) as usize;
+                let index: usize  = ((occupied.wrapping_mul(magic)) >> shift) as usize;
                 self.rooks[offset+index] = slider_mask(sq, &deltas, occupied);
 
                 occupied = (occupied.wrapping_sub(mask)) & mask;
@@ -173,11 +175,11 @@ impl AttackTable {
             }
         
             
-            self.rook_magics[sq] = Magic {
-                mask: mask,
-                magic: ROOK_MAGICS[sq],
+            self.rook_magics[sq.index()] = Magic {
+                mask,
+                magic,
                 offset: offset as u32,
-                shift: shift
+                shift
             };
             
             // offset depends on the number of masked bits
@@ -188,7 +190,7 @@ impl AttackTable {
     fn init_bishops(&mut self) {
         let deltas = [-7, 7, -9, 9];
         let mut offset: usize = 0;
-        for sq in 0..64 {
+        for (sq, &magic) in Square::iter().zip(BISHOP_MAGICS.iter()) {
             // generate the mask
             let edge_mask: Bitboard = ((BB_RANK_1 | BB_RANK_8) & !rank_bb(sq)) | (BB_FILE_A | BB_FILE_H) & !file_bb(sq);
             let mask: Bitboard = slider_mask(sq, &deltas, BB_NONE) & !edge_mask;
@@ -199,7 +201,7 @@ impl AttackTable {
             // iterate over every subset of the mask and set the attack mask in the lookup table
             let mut occupied: Bitboard = BB_NONE;
             loop {
-                let index: usize  = ((occupied.wrapping_mul(BISHOP_MAGICS[sq])) >> shift) as usize;
+                let index: usize  = ((occupied.wrapping_mul(magic)) >> shift) as usize;
                 self.bishops[offset+index] = slider_mask(sq, &deltas, occupied);
 
                 occupied = (occupied.wrapping_sub(mask)) & mask;
@@ -209,11 +211,11 @@ impl AttackTable {
             }
         
             
-            self.bishop_magics[sq] = Magic {
-                mask: mask,
-                magic: BISHOP_MAGICS[sq],
+            self.bishop_magics[sq.index()] = Magic {
+                mask,
+                magic,
                 offset: offset as u32,
-                shift: shift
+                shift
             };
             
             // offset depends on the number of masked bits
@@ -224,35 +226,27 @@ impl AttackTable {
     fn init_rays(&mut self) {
         let directions = [-9, -8, -7, -1, 1, 7, 8, 9];
 
-        let mut from_square: i8 = 0;
-        while from_square < 64 {
+        for from_square in Square::iter() {
             for dir in directions {
                 // generate bitmask
-                let mut bitmask: Bitboard = BB_NONE;
-                let mut prev_square: i8 = from_square;
-
-                bitmask |= square_bb(prev_square as Square);
-
-                let mut curr_square: i8 = prev_square + dir;
-
-                while curr_square >= 0 && curr_square <= 63 && chebyshev_distance(prev_square as Square, curr_square as Square) < 2 {
-                    bitmask |= square_bb(curr_square as Square);
+                let mut bitmask: Bitboard = square_bb(from_square);
+                let mut prev_square: Square = from_square;
 
+                while let Some(curr_square) = prev_square.offset(dir).filter(|&sq| chebyshev_distance(prev_square, sq) < 2) {
+                    bitmask |= square_bb(curr_square);
                     prev_square = curr_square;
-                    curr_square += dir;
                 }
 
                 // for every square on ray, rays[from_square][to_square] = bitmask
-                curr_square = prev_square;
+                let mut curr_square: Square = prev_square;
                 while curr_square != from_square {
-                    self.rays[from_square as Square][curr_square as Square] = bitmask;
+                    self.rays[from_square.index()][curr_square.index()] = bitmask;
 
-                    curr_square -= dir;
+                    curr_square = curr_square.offset_unchecked(-dir);
                 }
 
-                self.rays[from_square as Square][from_square as Square] = square_bb(from_square as Square);
+                self.rays[from_square.index()][from_square.index()] = square_bb(from_square);
             }
-            from_square += 1;
         }
     }
 }
@@ -263,15 +257,13 @@ impl AttackTable {
 fn slider_mask(sq: Square, deltas: &[i8], occupied: Bitboard) -> Bitboard {
     let mut attacks = BB_NONE;
     for delta in deltas {
-        let mut curr_sq = sq as i8;
+        let mut curr_sq: Square = sq;
 
-        loop {
-            if curr_sq + delta < 0 || curr_sq + delta > 63 || chebyshev_distance(curr_sq as Square, (curr_sq + delta) as Square) > 2 {
-                break;
-            }
-            curr_sq += delta;
+        // stop at the edge of the board, or when the step wrapped around to the other side
+        while let Some(next_sq) = curr_sq.offset(*delta).filter(|&next_sq| chebyshev_distance(curr_sq, next_sq) <= 2) {
+            curr_sq = next_sq;
 
-            let curr_bb: Bitboard = square_bb(curr_sq as Square);
+            let curr_bb: Bitboard = square_bb(curr_sq);
             attacks |= curr_bb;
 
             if occupied & curr_bb > 0 {
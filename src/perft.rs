@@ -1,22 +1,36 @@
+mod hash_table;
+
+use std::{collections::VecDeque, ops::AddAssign, sync::Mutex, thread, time::Instant};
+
 use crate::types::*;
-use crate::movegen::gen_legal_moves;
+use crate::bitboard::*;
+use crate::move_list::MoveList;
+use crate::movegen::{gen_captures, gen_quiets, gen_evasions};
 use crate::position::Position;
 
-// check move generation against positions from https://www.chessprogramming.org/Perft_Results
+pub use hash_table::{PerftTable, PerftTableError};
 
-struct PerftResult {
-    fen: &'static str,
-    depth: u8,
-    move_count: u64
+// check move generation against positions from https://www.chessprogramming.org/Perft_Results,
+// and chess960 positions from https://www.chessprogramming.org/Chess960_Perft_Results
+
+pub struct PerftResult {
+    pub fen: &'static str,
+    pub depth: u8,
+    pub move_count: u64
 }
 
-const PERFT_RESULTS: [PerftResult; 6] = [
+pub const PERFT_RESULTS: [PerftResult; 11] = [
     PerftResult {fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", depth: 6, move_count: 119_060_324},
     PerftResult {fen: "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -", depth: 5, move_count: 193_690_690},
     PerftResult {fen: "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - ", depth: 7, move_count: 178_633_661},
     PerftResult {fen: "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", depth: 6, move_count: 706_045_033},
     PerftResult {fen: "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", depth: 5, move_count: 89_941_194},
-    PerftResult {fen: "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10", depth: 5, move_count: 164_075_551}
+    PerftResult {fen: "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10", depth: 5, move_count: 164_075_551},
+    PerftResult {fen: "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9", depth: 5, move_count: 8_146_062},
+    PerftResult {fen: "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9", depth: 5, move_count: 16_253_601},
+    PerftResult {fen: "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9", depth: 5, move_count: 6_417_013},
+    PerftResult {fen: "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9", depth: 5, move_count: 9_183_776},
+    PerftResult {fen: "1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9", depth: 5, move_count: 34_030_312}
 ];
 
 pub fn perft(pos: &Position, depth: u8) -> u64 {
@@ -24,31 +38,385 @@ pub fn perft(pos: &Position, depth: u8) -> u64 {
         return 1;
     }
 
-    let mut move_list: [Move; 256] = [Move::default(); 256];
-    let mut move_count: usize = 0;
-
-    gen_legal_moves(pos, &mut move_list, &mut move_count);
-
     let mut total_count: u64 = 0;
-    for i in 0..move_count {
+    for mv in pos.legal_moves().iter() {
         let mut updated_pos = *pos;
-        updated_pos.make(&move_list[i]);
+        updated_pos.make(mv);
 
         let nodes: u64 = perft(&updated_pos, depth-1);
 
         total_count += nodes;
     }
 
-    return total_count;
+    total_count
+}
+
+// same as perft, but walks the tree with make/unmake on a single position instead of copying it for every move
+pub fn perft_unmake(pos: &mut Position, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let mut total_count: u64 = 0;
+    for mv in pos.legal_moves().iter() {
+        let undo = pos.make(mv);
+        total_count += perft_unmake(pos, depth-1);
+        pos.unmake(mv, &undo);
+    }
+
+    total_count
+}
+
+// perft over captures and quiets generated separately, checking at every node that together they are exactly the legal
+// moves, and that gives_check agrees with making each move and testing for check
+pub fn perft_staged(pos: &Position, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let legal_moves: MoveList = pos.legal_moves();
+    let mut captures = MoveList::new();
+    let mut quiets = MoveList::new();
+    gen_captures(pos, &mut captures);
+    gen_quiets(pos, &mut quiets);
+
+    assert_eq!(captures.len() + quiets.len(), legal_moves.len(), "staged move count mismatch in {}", pos.to_fen());
+    for mv in legal_moves.iter() {
+        assert!(captures.contains(mv) != quiets.contains(mv), "{} not generated exactly once in {}", pos.move_to_uci(mv), pos.to_fen());
+    }
+
+    if pos.is_check() {
+        let mut evasions = MoveList::new();
+        gen_evasions(pos, &mut evasions);
+        assert_eq!(evasions, legal_moves, "evasions differ from legal moves in {}", pos.to_fen());
+    }
+
+    let mut total_count: u64 = 0;
+    for mv in captures.iter().chain(quiets.iter()) {
+        let mut updated_pos = *pos;
+        updated_pos.make(mv);
+        assert_eq!(pos.gives_check(mv), updated_pos.is_check(), "gives_check wrong for {} in {}", pos.move_to_uci(mv), pos.to_fen());
+
+        total_count += perft_staged(&updated_pos, depth-1);
+    }
+
+    total_count
+}
+
+// node count below each root move, keyed by the move in UCI notation and sorted by it, like the "divide" output of other
+// engines. the counts add up to perft(pos, depth)
+pub fn perft_divide(pos: &Position, depth: u8) -> Vec<(String, u64)> {
+    divide_with(pos, depth, perft)
+}
+
+// perft_divide counting each subtree with perft_hash
+pub fn perft_divide_hash(pos: &Position, depth: u8, table: &PerftTable) -> Vec<(String, u64)> {
+    divide_with(pos, depth, |pos, depth| perft_hash(pos, depth, table))
+}
+
+fn divide_with<F: Fn(&Position, u8) -> u64>(pos: &Position, depth: u8, perft_fn: F) -> Vec<(String, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+
+    let mut divide: Vec<(String, u64)> = pos.legal_moves().iter().map(|mv| {
+        let mut updated_pos = *pos;
+        updated_pos.make(mv);
+
+        (pos.move_to_uci(mv), perft_fn(&updated_pos, depth-1))
+    }).collect();
+
+    divide.sort();
+    divide
+}
+
+// counters for the moves made at the last ply of a perft, as listed on https://www.chessprogramming.org/Perft_Results.
+// captures include en passant, and a discovered check is one where the piece that moved isn't among the checkers, so a
+// double check by the moved piece and the piece it uncovered is not also counted as discovered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PerftStats {
+    pub nodes: u64,
+    pub captures: u64,
+    pub en_passants: u64,
+    pub castles: u64,
+    pub promotions: u64,
+    pub checks: u64,
+    pub discovered_checks: u64,
+    pub double_checks: u64,
+    pub checkmates: u64
+}
+
+impl PerftStats {
+    #[allow(clippy::too_many_arguments)]
+    pub const fn new(nodes: u64, captures: u64, en_passants: u64, castles: u64, promotions: u64, checks: u64, discovered_checks: u64, double_checks: u64, checkmates: u64) -> Self {
+        PerftStats { nodes, captures, en_passants, castles, promotions, checks, discovered_checks, double_checks, checkmates }
+    }
+
+    // these stats with the counters a reference table leaves out set to 0
+    fn listed(self, listed: StatsListed) -> PerftStats {
+        match listed {
+            StatsListed::All => self,
+            StatsListed::NoDiscoveredChecks => PerftStats { discovered_checks: 0, double_checks: 0, ..self },
+            StatsListed::NodesOnly => PerftStats { nodes: self.nodes, ..PerftStats::default() }
+        }
+    }
+}
+
+impl AddAssign for PerftStats {
+    fn add_assign(&mut self, other: PerftStats) {
+        self.nodes += other.nodes;
+        self.captures += other.captures;
+        self.en_passants += other.en_passants;
+        self.castles += other.castles;
+        self.promotions += other.promotions;
+        self.checks += other.checks;
+        self.discovered_checks += other.discovered_checks;
+        self.double_checks += other.double_checks;
+        self.checkmates += other.checkmates;
+    }
+}
+
+// perft that also sorts the moves at the last ply into categories, to narrow a wrong node count down to a kind of move
+pub fn perft_stats(pos: &Position, depth: u8) -> PerftStats {
+    let mut stats = PerftStats::default();
+    if depth == 0 {
+        stats.nodes = 1;
+        return stats;
+    }
+
+    for mv in pos.legal_moves().iter() {
+        let mut updated_pos = *pos;
+        updated_pos.make(mv);
+
+        if depth > 1 {
+            stats += perft_stats(&updated_pos, depth-1);
+            continue;
+        }
+
+        stats.nodes += 1;
+        if mv.is_en_passant() || (!mv.is_castle() && pos.piece_at(mv.to_square()).is_some()) {
+            stats.captures += 1;
+        }
+        if mv.is_en_passant() {
+            stats.en_passants += 1;
+        }
+        if mv.is_castle() {
+            stats.castles += 1;
+        }
+        if mv.is_promotion() {
+            stats.promotions += 1;
+        }
+
+        if updated_pos.is_check() {
+            // after castling the rook is the piece that moved to give check
+            let moved_to: Square = if mv.is_castle() {castling_targets(mv.from_square(), mv.to_square()).1} else {mv.to_square()};
+
+            stats.checks += 1;
+            if updated_pos.checkers & square_bb(moved_to) == 0 {
+                stats.discovered_checks += 1;
+            }
+            if popcount(updated_pos.checkers) > 1 {
+                stats.double_checks += 1;
+            }
+            if !updated_pos.has_legal_moves() {
+                stats.checkmates += 1;
+            }
+        }
+    }
+
+    stats
+}
+
+// perft that splits the root moves between worker threads
+pub fn fast_perft(pos: Position, depth: u8, thread_count: u8) -> u64 {
+    split_perft(pos, depth, thread_count, perft)
+}
+
+// perft that reuses the node counts of positions it has already seen at the same depth, which happen all the time
+// through transpositions. gives the same result as perft
+pub fn perft_hash(pos: &Position, depth: u8, table: &PerftTable) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let legal_moves: MoveList = pos.legal_moves();
+    if depth == 1 {
+        return legal_moves.len() as u64;
+    }
+
+    if let Some(nodes) = table.probe(pos.hash, depth) {
+        return nodes;
+    }
+
+    let mut total_count: u64 = 0;
+    for mv in legal_moves.iter() {
+        let mut updated_pos = *pos;
+        updated_pos.make(mv);
+
+        total_count += perft_hash(&updated_pos, depth-1, table);
+    }
+
+    table.store(pos.hash, depth, total_count);
+    total_count
+}
+
+// fast_perft with every thread sharing one hash table
+pub fn fast_perft_hash(pos: Position, depth: u8, thread_count: u8, table: &PerftTable) -> u64 {
+    split_perft(pos, depth, thread_count, |pos, depth| perft_hash(pos, depth, table))
+}
+
+// hand the root moves out to worker threads one at a time, counting the nodes below each with perft_fn
+fn split_perft<F: Fn(&Position, u8) -> u64 + Sync>(pos: Position, depth: u8, thread_count: u8, perft_fn: F) -> u64 {
+    let queue: Mutex<VecDeque<Move>> = Mutex::new(pos.legal_moves().into_iter().collect());
+
+    thread::scope(|scope| {
+        let mut handles = vec![];
+
+        for _ in 0..thread_count {
+            let handle = scope.spawn(|| {
+                let mut thread_sum: u64 = 0;
+
+                loop {
+                    let move_p = {
+                        let mut queue = queue.lock().unwrap();
+                        queue.pop_front()
+                    };
+
+                    if let Some(mv) = move_p {
+                        let mut updated_pos = pos;
+                        updated_pos.make(&mv);
+
+                        thread_sum += perft_fn(&updated_pos, depth-1);
+                    } else {
+                        break;
+                    }
+                }
+
+                thread_sum
+            });
+
+            handles.push(handle);
+        }
+
+        let mut perft_result = 0;
+        for handle in handles {
+            perft_result += handle.join().unwrap();
+        }
+
+        perft_result
+    })
+}
+
+// which counters a reference table gives for a position
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum StatsListed {
+    All,
+    NoDiscoveredChecks,
+    NodesOnly
+}
+
+pub struct PerftStatsResult {
+    pub fen: &'static str,
+    pub listed: StatsListed,
+    // stats for depth 1, 2, ...
+    pub stats: &'static [PerftStats]
+}
+
+// the tables from https://www.chessprogramming.org/Perft_Results for the first six PERFT_RESULTS positions, to the depths
+// that run in a few seconds. the page lists 0 discovered and double checks for position 4 even though both happen there,
+// and only node counts for positions 5 and 6
+pub const PERFT_STATS_RESULTS: [PerftStatsResult; 6] = [
+    PerftStatsResult {fen: PERFT_RESULTS[0].fen, listed: StatsListed::All, stats: &[
+        PerftStats::new(20, 0, 0, 0, 0, 0, 0, 0, 0),
+        PerftStats::new(400, 0, 0, 0, 0, 0, 0, 0, 0),
+        PerftStats::new(8_902, 34, 0, 0, 0, 12, 0, 0, 0),
+        PerftStats::new(197_281, 1_576, 0, 0, 0, 469, 0, 0, 8),
+        PerftStats::new(4_865_609, 82_719, 258, 0, 0, 27_351, 6, 0, 347)
+    ]},
+    PerftStatsResult {fen: PERFT_RESULTS[1].fen, listed: StatsListed::All, stats: &[
+        PerftStats::new(48, 8, 0, 2, 0, 0, 0, 0, 0),
+        PerftStats::new(2_039, 351, 1, 91, 0, 3, 0, 0, 0),
+        PerftStats::new(97_862, 17_102, 45, 3_162, 0, 993, 0, 0, 1),
+        PerftStats::new(4_085_603, 757_163, 1_929, 128_013, 15_172, 25_523, 42, 6, 43)
+    ]},
+    PerftStatsResult {fen: PERFT_RESULTS[2].fen, listed: StatsListed::All, stats: &[
+        PerftStats::new(14, 1, 0, 0, 0, 2, 0, 0, 0),
+        PerftStats::new(191, 14, 0, 0, 0, 10, 0, 0, 0),
+        PerftStats::new(2_812, 209, 2, 0, 0, 267, 3, 0, 0),
+        PerftStats::new(43_238, 3_348, 123, 0, 0, 1_680, 106, 0, 17),
+        PerftStats::new(674_624, 52_051, 1_165, 0, 0, 52_950, 1_292, 3, 0),
+        PerftStats::new(11_030_083, 940_350, 33_325, 0, 7_552, 452_473, 26_067, 0, 2_733)
+    ]},
+    PerftStatsResult {fen: PERFT_RESULTS[3].fen, listed: StatsListed::NoDiscoveredChecks, stats: &[
+        PerftStats::new(6, 0, 0, 0, 0, 0, 0, 0, 0),
+        PerftStats::new(264, 87, 0, 6, 48, 10, 0, 0, 0),
+        PerftStats::new(9_467, 1_021, 4, 0, 120, 38, 0, 0, 22),
+        PerftStats::new(422_333, 131_393, 0, 7_795, 60_032, 15_492, 0, 0, 5),
+        PerftStats::new(15_833_292, 2_046_173, 6_512, 0, 329_464, 200_568, 0, 0, 50_562)
+    ]},
+    PerftStatsResult {fen: PERFT_RESULTS[4].fen, listed: StatsListed::NodesOnly, stats: &[
+        PerftStats::new(44, 0, 0, 0, 0, 0, 0, 0, 0),
+        PerftStats::new(1_486, 0, 0, 0, 0, 0, 0, 0, 0),
+        PerftStats::new(62_379, 0, 0, 0, 0, 0, 0, 0, 0),
+        PerftStats::new(2_103_487, 0, 0, 0, 0, 0, 0, 0, 0)
+    ]},
+    PerftStatsResult {fen: PERFT_RESULTS[5].fen, listed: StatsListed::NodesOnly, stats: &[
+        PerftStats::new(46, 0, 0, 0, 0, 0, 0, 0, 0),
+        PerftStats::new(2_079, 0, 0, 0, 0, 0, 0, 0, 0),
+        PerftStats::new(89_890, 0, 0, 0, 0, 0, 0, 0, 0),
+        PerftStats::new(3_894_594, 0, 0, 0, 0, 0, 0, 0, 0)
+    ]}
+];
+
+pub fn check_perft_stats() {
+    for stats_res in PERFT_STATS_RESULTS {
+        let pos = Position::from_fen(stats_res.fen).unwrap();
+        for (depth, &expected) in (1..).zip(stats_res.stats) {
+            assert_eq!(perft_stats(&pos, depth).listed(stats_res.listed), expected, "depth {} of '{}'", depth, stats_res.fen);
+        }
+        println!("Stats '{}' passed", stats_res.fen);
+    }
+    println!("Perft stats passed");
 }
 
 pub fn check_movegen_correctness() {
     for p_res in PERFT_RESULTS {
-        let mut pos = Position::new();
-        pos.parse_fen(p_res.fen);
+        let pos = Position::from_fen(p_res.fen).unwrap();
+        assert_eq!(Position::from_fen(&pos.to_fen()), Ok(pos));
 
+        let start = Instant::now();
         assert_eq!(p_res.move_count, perft(&pos, p_res.depth));
-        println!("Test '{}' passed", p_res.fen);
+        let copy_make_time = start.elapsed();
+
+        // make/unmake has to give the same count and leave the position exactly as it found it
+        let mut unmake_pos = pos;
+        let start = Instant::now();
+        assert_eq!(p_res.move_count, perft_unmake(&mut unmake_pos, p_res.depth));
+        let unmake_time = start.elapsed();
+        assert_eq!(unmake_pos, pos);
+
+        assert_eq!(perft(&pos, p_res.depth - 1), perft_staged(&pos, p_res.depth - 1));
+        println!("Test '{}' passed, copy-make {:.2?}, make/unmake {:.2?}", p_res.fen, copy_make_time, unmake_time);
     }
     println!("Movegen passed");
-}
\ No newline at end of file
+}
+
+// hashed perft, on one thread and then shared between several, against every PERFT_RESULTS entry
+pub fn check_perft_hash() {
+    let mut table = PerftTable::new(64).unwrap();
+
+    for p_res in PERFT_RESULTS {
+        let pos = Position::from_fen(p_res.fen).unwrap();
+
+        table.clear();
+        let start = Instant::now();
+        assert_eq!(p_res.move_count, perft_hash(&pos, p_res.depth, &table));
+        println!("Hashed '{}' passed in {:.2?}, {:.1}% of {} probes hit", p_res.fen, start.elapsed(), table.hit_rate() * 100.0, table.probes());
+
+        table.clear();
+        let start = Instant::now();
+        assert_eq!(p_res.move_count, fast_perft_hash(pos, p_res.depth, 4, &table));
+        println!("Shared '{}' passed in {:.2?}, {:.1}% of {} probes hit", p_res.fen, start.elapsed(), table.hit_rate() * 100.0, table.probes());
+    }
+    println!("Hashed perft passed");
+}
@@ -0,0 +1,115 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// transposition table for perft, mapping a zobrist hash and a depth to the node count below that position. it can be
+// shared between threads without locking: each entry stores its key xored with its data, so an entry torn by two
+// threads writing at once no longer matches its key and reads as a miss, see https://www.chessprogramming.org/Shared_Hash_Table
+pub struct PerftTable {
+    entries: Vec<PerftEntry>,
+    probes: AtomicU64,
+    hits: AtomicU64
+}
+
+// error returned when a perft table of the requested size can't be made
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PerftTableError {
+    TooLarge { size_mb: usize },
+    AllocationFailed { size_mb: usize }
+}
+
+impl fmt::Display for PerftTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PerftTableError::TooLarge { size_mb } => write!(f, "hash table of {} MB is too large", size_mb),
+            PerftTableError::AllocationFailed { size_mb } => write!(f, "couldn't allocate {} MB for the hash table", size_mb)
+        }
+    }
+}
+
+impl std::error::Error for PerftTableError {}
+
+#[derive(Default)]
+struct PerftEntry {
+    key: AtomicU64,
+    // node count in the upper 56 bits, depth in the lower 8
+    data: AtomicU64
+}
+
+impl PerftTable {
+    // table using at most size_mb megabytes. the entry count is rounded down to a power of two. fails instead of
+    // panicking or aborting if the size overflows or the memory can't be allocated
+    pub fn new(size_mb: usize) -> Result<Self, PerftTableError> {
+        let size_bytes: usize = size_mb.checked_mul(1024 * 1024).ok_or(PerftTableError::TooLarge { size_mb })?;
+        let max_entries: usize = (size_bytes / std::mem::size_of::<PerftEntry>()).max(1);
+        let entry_count: usize = 1 << max_entries.ilog2();
+
+        let mut entries: Vec<PerftEntry> = Vec::new();
+        entries.try_reserve_exact(entry_count).map_err(|_| PerftTableError::AllocationFailed { size_mb })?;
+        entries.extend((0..entry_count).map(|_| PerftEntry::default()));
+
+        Ok(PerftTable {
+            entries,
+            probes: AtomicU64::new(0),
+            hits: AtomicU64::new(0)
+        })
+    }
+
+    // forget every stored count and reset the hit statistics
+    pub fn clear(&mut self) {
+        for entry in self.entries.iter_mut() {
+            *entry = PerftEntry::default();
+        }
+        self.probes = AtomicU64::new(0);
+        self.hits = AtomicU64::new(0);
+    }
+
+    // node count stored for a position at a depth, if there is one
+    #[inline]
+    pub fn probe(&self, hash: u64, depth: u8) -> Option<u64> {
+        let entry: &PerftEntry = self.entry(hash);
+        let key: u64 = entry.key.load(Ordering::Relaxed);
+        let data: u64 = entry.data.load(Ordering::Relaxed);
+
+        self.probes.fetch_add(1, Ordering::Relaxed);
+        if key ^ data == hash && data & 0xff == depth as u64 {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            Some(data >> 8)
+        } else {
+            None
+        }
+    }
+
+    // store a node count, always replacing whatever was in the entry
+    #[inline]
+    pub fn store(&self, hash: u64, depth: u8, nodes: u64) {
+        let entry: &PerftEntry = self.entry(hash);
+        let data: u64 = (nodes << 8) | depth as u64;
+
+        entry.key.store(hash ^ data, Ordering::Relaxed);
+        entry.data.store(data, Ordering::Relaxed);
+    }
+
+    pub fn probes(&self) -> u64 {
+        self.probes.load(Ordering::Relaxed)
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    // fraction of probes that found a count, 0 if nothing was probed yet
+    pub fn hit_rate(&self) -> f64 {
+        let probes: u64 = self.probes();
+        if probes == 0 {0.0} else {self.hits() as f64 / probes as f64}
+    }
+
+    // size of the table in bytes
+    pub fn size_bytes(&self) -> usize {
+        self.entries.len() * std::mem::size_of::<PerftEntry>()
+    }
+
+    #[inline]
+    fn entry(&self, hash: u64) -> &PerftEntry {
+        &self.entries[hash as usize & (self.entries.len() - 1)]
+    }
+}
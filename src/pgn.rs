@@ -0,0 +1,102 @@
+pub mod reader;
+mod writer;
+
+use crate::types::*;
+use crate::position::Position;
+use crate::game::{Game, IllegalMove};
+
+pub use reader::{PgnReader, PgnError, PgnErrorKind};
+
+// a game read from (or to be written to) PGN, with its tags, comments, NAGs and variations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnGame {
+    // tag pairs in the order they appeared
+    pub tags: Vec<(String, String)>,
+    pub start: Position,
+    pub moves: Vec<PgnNode>,
+    // result token from the movetext: 1-0, 0-1, 1/2-1/2 or *
+    pub result: String,
+}
+
+// a move with everything attached to it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnNode {
+    pub mv: Move,
+    pub nags: Vec<u8>,
+    // comments before the move, only used for the first move of a game or variation
+    pub starting_comments: Vec<String>,
+    // comments after the move
+    pub comments: Vec<String>,
+    // alternatives to this move, each one starting from the position before it
+    pub variations: Vec<Vec<PgnNode>>,
+}
+
+impl PgnNode {
+    pub fn new(mv: Move) -> Self {
+        Self {
+            mv,
+            nags: Vec::new(),
+            starting_comments: Vec::new(),
+            comments: Vec::new(),
+            variations: Vec::new(),
+        }
+    }
+}
+
+impl PgnGame {
+    // returns the value of a tag, if present
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| value.as_str())
+    }
+
+    // main line of a game, without tags. the seven tag roster is filled with unknown values when written
+    pub fn from_game(game: &Game) -> Self {
+        Self {
+            tags: Vec::new(),
+            start: *game.start_position(),
+            moves: game.moves().iter().map(|&mv| PgnNode::new(mv)).collect(),
+            result: game.result().to_string(),
+        }
+    }
+
+    // replay the main line into a Game
+    pub fn to_game(&self) -> Result<Game, IllegalMove> {
+        let mut game = Game::from_position(self.start);
+        for node in self.moves.iter() {
+            game.make_move(&node.mv)?;
+        }
+        Ok(game)
+    }
+}
+
+// read files with known contents, including broken games that must be reported without disturbing the games after them
+pub fn check_pgn_correctness() {
+    let read = |pgn: &str| -> Vec<Result<PgnGame, PgnError>> { PgnReader::new(pgn.as_bytes()).collect() };
+
+    let games = read("[Event \"a\"]\n\n1. e4 {best by test} e5 (1... c5 $1 2. Nf3) 2. Nf3 ; main line\nNc6 1-0\n");
+    assert_eq!(games.len(), 1);
+    let game = games[0].as_ref().unwrap();
+    assert_eq!((game.tag("Event"), game.result.as_str(), game.moves.len()), (Some("a"), "1-0", 4));
+    assert_eq!((game.moves[0].comments.len(), game.moves[1].variations[0].len(), game.moves[1].variations[0][0].nags.len()), (1, 2, 1));
+    assert_eq!(game.to_game().unwrap().position().to_fen(), "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3");
+
+    // a bad tag is skipped on its own, so the rest of the tags stay in the same game
+    let games = read("[Event x]\n[Site \"y\"]\n[Round \"1\"]\n\n1. e4 e5 1-0\n\n[Event \"g2\"]\n\n1. d4 d5 0-1\n");
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0].as_ref().unwrap_err().kind, PgnErrorKind::InvalidTag);
+    assert_eq!(games[0].as_ref().unwrap_err().game_index, 0);
+    assert_eq!(games[1].as_ref().unwrap().tag("Event"), Some("g2"));
+
+    // a FEN tag without kings is reported instead of generating moves from it
+    let games = read("[FEN \"8/8/8/8/8/8/4P3/8 w - - 0 1\"]\n\n1. e4 *\n\n[Event \"g2\"]\n\n1. d4 *\n");
+    assert_eq!(games.len(), 2);
+    assert!(matches!(games[0].as_ref().unwrap_err().kind, PgnErrorKind::InvalidPosition(_)));
+    assert_eq!(games[1].as_ref().unwrap().moves.len(), 1);
+
+    // an illegal move only loses its own game
+    let games = read("1. e4 e5 2. Ke3 *\n\n1. d4 *\n");
+    assert_eq!(games.len(), 2);
+    assert!(matches!(games[0].as_ref().unwrap_err().kind, PgnErrorKind::InvalidMove(_)));
+    assert_eq!(games[1].as_ref().unwrap().moves.len(), 1);
+    println!("PGN passed");
+}
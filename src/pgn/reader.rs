@@ -0,0 +1,527 @@
+use std::fmt;
+use std::io::BufRead;
+
+use crate::fen::FenError;
+use crate::position::Position;
+use crate::san::SanError;
+use crate::validate::{PositionError, Violation};
+use super::{PgnGame, PgnNode};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgnErrorKind {
+    // the underlying reader failed. nothing more is read after this
+    Io(String),
+    InvalidTag,
+    InvalidFen(FenError),
+    // the FEN tag parsed but the position fails validation, so its moves can't be replayed
+    InvalidPosition(Vec<Violation>),
+    InvalidMove(SanError),
+    UnexpectedCharacter(char),
+    UnexpectedToken(String),
+    UnterminatedComment,
+    UnterminatedString,
+    UnbalancedVariation,
+}
+
+// error in one game of a PGN file. game_index counts games from 0, line counts from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnError {
+    pub game_index: usize,
+    pub line: usize,
+    pub kind: PgnErrorKind,
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "game {} (line {}): ", self.game_index, self.line)?;
+        match &self.kind {
+            PgnErrorKind::Io(err) => write!(f, "read error: {}", err),
+            PgnErrorKind::InvalidTag => write!(f, "invalid tag pair"),
+            PgnErrorKind::InvalidFen(err) => write!(f, "{}", err),
+            PgnErrorKind::InvalidPosition(violations) => write!(f, "{}", PositionError::Invalid(violations.clone())),
+            PgnErrorKind::InvalidMove(err) => write!(f, "{}", err),
+            PgnErrorKind::UnexpectedCharacter(ch) => write!(f, "unexpected character '{}'", ch),
+            PgnErrorKind::UnexpectedToken(token) => write!(f, "unexpected '{}'", token),
+            PgnErrorKind::UnterminatedComment => write!(f, "unterminated comment"),
+            PgnErrorKind::UnterminatedString => write!(f, "unterminated string"),
+            PgnErrorKind::UnbalancedVariation => write!(f, "unbalanced variation")
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    TagOpen,
+    TagClose,
+    Str(String),
+    // moves, move numbers and tag names
+    Symbol(String),
+    Nag(u8),
+    Comment(String),
+    VariationOpen,
+    VariationClose,
+    GameResult(String),
+}
+
+// splits PGN text into tokens, one line at a time
+struct Lexer<R: BufRead> {
+    reader: R,
+    line: Vec<char>,
+    pos: usize,
+    line_number: usize,
+    // line the last token started on
+    token_line: usize,
+    pushed_back: Option<Token>,
+    failed: bool,
+}
+
+impl<R: BufRead> Lexer<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: Vec::new(),
+            pos: 0,
+            line_number: 0,
+            token_line: 0,
+            pushed_back: None,
+            failed: false,
+        }
+    }
+
+    // returns the next character without consuming it, reading more lines as needed
+    fn peek_char(&mut self) -> Result<Option<char>, PgnErrorKind> {
+        while self.pos >= self.line.len() {
+            if self.failed {
+                return Ok(None);
+            }
+
+            let mut buf = String::new();
+            match self.reader.read_line(&mut buf) {
+                Ok(0) => return Ok(None),
+                Ok(_) => (),
+                Err(err) => {
+                    self.failed = true;
+                    return Err(PgnErrorKind::Io(err.to_string()));
+                }
+            }
+            self.line_number += 1;
+
+            // lines starting with % are escaped and ignored
+            if buf.starts_with('%') {
+                continue;
+            }
+
+            self.line = buf.chars().collect();
+            self.pos = 0;
+        }
+
+        Ok(Some(self.line[self.pos]))
+    }
+
+    fn push_back(&mut self, token: Token) {
+        self.pushed_back = Some(token);
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token>, PgnErrorKind> {
+        if let Some(token) = self.pushed_back.take() {
+            return Ok(Some(token));
+        }
+
+        // skip whitespace and move number periods
+        while let Some(ch) = self.peek_char()? {
+            if !ch.is_whitespace() && ch != '.' {
+                break;
+            }
+            self.pos += 1;
+        }
+
+        self.token_line = self.line_number;
+        let ch = match self.peek_char()? {
+            Some(ch) => ch,
+            None => return Ok(None)
+        };
+        self.pos += 1;
+
+        let token = match ch {
+            '[' => Token::TagOpen,
+            ']' => Token::TagClose,
+            '(' => Token::VariationOpen,
+            ')' => Token::VariationClose,
+            '*' => Token::GameResult("*".to_string()),
+            '{' => {
+                let mut comment = String::new();
+                loop {
+                    match self.peek_char()? {
+                        Some('}') => break,
+                        Some(ch) => comment.push(ch),
+                        None => return Err(PgnErrorKind::UnterminatedComment)
+                    }
+                    self.pos += 1;
+                }
+                self.pos += 1;
+                Token::Comment(comment.split_whitespace().collect::<Vec<&str>>().join(" "))
+            }
+            ';' => {
+                // rest of the line is a comment
+                let comment: String = self.line[self.pos..].iter().collect();
+                self.pos = self.line.len();
+                Token::Comment(comment.trim().to_string())
+            }
+            '"' => {
+                // strings can't span lines
+                let mut value = String::new();
+                loop {
+                    let ch = match self.line.get(self.pos) {
+                        Some('\n') | None => return Err(PgnErrorKind::UnterminatedString),
+                        Some(&ch) => ch
+                    };
+                    self.pos += 1;
+
+                    match ch {
+                        '"' => break,
+                        '\\' => {
+                            if let Some(&escaped) = self.line.get(self.pos) {
+                                value.push(escaped);
+                                self.pos += 1;
+                            }
+                        }
+                        _ => value.push(ch)
+                    }
+                }
+                Token::Str(value)
+            }
+            '$' => {
+                let digits: String = self.take_while(|ch| ch.is_ascii_digit());
+                Token::Nag(digits.parse().map_err(|_| PgnErrorKind::UnexpectedCharacter('$'))?)
+            }
+            '!' | '?' => {
+                // traditional suffix annotations
+                let suffix: String = std::iter::once(ch).chain(self.take_while(|ch| ch == '!' || ch == '?').chars()).collect();
+                Token::Nag(match suffix.as_str() {
+                    "!" => 1,
+                    "?" => 2,
+                    "!!" => 3,
+                    "??" => 4,
+                    "!?" => 5,
+                    "?!" => 6,
+                    _ => return Err(PgnErrorKind::UnexpectedToken(suffix))
+                })
+            }
+            _ if ch.is_ascii_alphanumeric() => {
+                let symbol: String = std::iter::once(ch).chain(self.take_while(is_symbol_char).chars()).collect();
+                match symbol.as_str() {
+                    "1-0" | "0-1" | "1/2-1/2" => Token::GameResult(symbol),
+                    _ => Token::Symbol(symbol)
+                }
+            }
+            _ => return Err(PgnErrorKind::UnexpectedCharacter(ch))
+        };
+
+        Ok(Some(token))
+    }
+
+    // consume characters from the current line while they match
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> String {
+        let start = self.pos;
+        while self.pos < self.line.len() && pred(self.line[self.pos]) {
+            self.pos += 1;
+        }
+        self.line[start..self.pos].iter().collect()
+    }
+}
+
+#[inline]
+fn is_symbol_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || "_+#=:-/".contains(ch)
+}
+
+// a line of moves being read. variations push a new one
+struct Line {
+    nodes: Vec<PgnNode>,
+    // position after the last move, and before it
+    pos: Position,
+    prev_pos: Position,
+    // comments waiting for the first move of the line
+    starting_comments: Vec<String>,
+}
+
+impl Line {
+    fn new(pos: Position) -> Self {
+        Self {
+            nodes: Vec::new(),
+            pos,
+            prev_pos: pos,
+            starting_comments: Vec::new(),
+        }
+    }
+}
+
+// reads games one at a time from PGN text. an error in one game is returned in its place and reading carries on
+// with the next game
+pub struct PgnReader<R: BufRead> {
+    lexer: Lexer<R>,
+    game_index: usize,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            lexer: Lexer::new(reader),
+            game_index: 0,
+        }
+    }
+
+    fn error(&self, kind: PgnErrorKind) -> PgnError {
+        PgnError {
+            game_index: self.game_index,
+            line: self.lexer.token_line,
+            kind,
+        }
+    }
+
+    // reads the next token, turning lexer failures into errors for the current game
+    fn next_token(&mut self) -> Result<Option<Token>, PgnError> {
+        self.lexer.next_token().map_err(|kind| self.error(kind))
+    }
+
+    // reads a tag pair after its opening bracket. a bad tag pair is skipped up to its closing bracket, so the rest of
+    // the tag section is still read as part of the same game
+    fn read_tag(&mut self) -> Result<(String, String), PgnError> {
+        let tag = self.read_tag_pair();
+        if tag.is_err() {
+            self.skip_tag();
+        }
+        tag
+    }
+
+    fn read_tag_pair(&mut self) -> Result<(String, String), PgnError> {
+        let name = match self.next_token()? {
+            Some(Token::Symbol(name)) => name,
+            token => return Err(self.invalid_tag(token))
+        };
+        let value = match self.next_token()? {
+            Some(Token::Str(value)) => value,
+            token => return Err(self.invalid_tag(token))
+        };
+        match self.next_token()? {
+            Some(Token::TagClose) => Ok((name, value)),
+            token => Err(self.invalid_tag(token))
+        }
+    }
+
+    // the token that didn't fit is put back for skip_tag, which may need to stop at it
+    fn invalid_tag(&mut self, token: Option<Token>) -> PgnError {
+        if let Some(token) = token {
+            self.lexer.push_back(token);
+        }
+        self.error(PgnErrorKind::InvalidTag)
+    }
+
+    // skip to the end of a bad tag pair, stopping before the next tag pair if its closing bracket is missing
+    fn skip_tag(&mut self) {
+        loop {
+            match self.lexer.next_token() {
+                Ok(Some(Token::TagClose)) | Ok(None) => break,
+                Ok(Some(Token::TagOpen)) => {
+                    self.lexer.push_back(Token::TagOpen);
+                    break;
+                }
+                _ => ()
+            }
+        }
+    }
+
+    fn read_game(&mut self) -> Option<Result<PgnGame, PgnError>> {
+        // the first error in the game is the one reported, the rest of the game is skipped
+        let mut error: Option<PgnError> = None;
+        let mut tags: Vec<(String, String)> = Vec::new();
+        let mut leading_comments: Vec<String> = Vec::new();
+
+        // tag pair section
+        let mut seen_anything = false;
+        loop {
+            let token = match self.next_token() {
+                Ok(Some(token)) => token,
+                Ok(None) => break,
+                Err(err) => {
+                    seen_anything = true;
+                    error.get_or_insert(err);
+                    continue;
+                }
+            };
+            seen_anything = true;
+
+            match token {
+                Token::TagOpen => match self.read_tag() {
+                    Ok(tag) => tags.push(tag),
+                    Err(err) => {
+                        error.get_or_insert(err);
+                    }
+                },
+                Token::Comment(comment) => leading_comments.push(comment),
+                token => {
+                    self.lexer.push_back(token);
+                    break;
+                }
+            }
+        }
+        if !seen_anything {
+            return None;
+        }
+
+        // start position
+        let mut start = Position::new();
+        if let Some((_, fen)) = tags.iter().find(|(name, _)| name == "FEN") {
+            match Position::from_fen_validated(fen) {
+                Ok(pos) => start = pos,
+                Err(PositionError::Fen(err)) => {
+                    error.get_or_insert(self.error(PgnErrorKind::InvalidFen(err)));
+                }
+                Err(PositionError::Invalid(violations)) => {
+                    error.get_or_insert(self.error(PgnErrorKind::InvalidPosition(violations)));
+                }
+            }
+        }
+        if tags.iter().any(|(name, value)| name == "Variant" && value.to_ascii_lowercase().contains("960")) {
+            start.chess960 = true;
+        }
+
+        // movetext
+        let mut lines: Vec<Line> = vec![Line::new(start)];
+        lines[0].starting_comments = leading_comments;
+        let mut skip_depth: usize = 0;
+        let mut result: Option<String> = None;
+
+        loop {
+            let token = match self.next_token() {
+                Ok(Some(token)) => token,
+                Ok(None) => break,
+                Err(err) => {
+                    error.get_or_insert(err);
+                    continue;
+                }
+            };
+
+            // after an error just find the end of the game
+            if error.is_some() {
+                match token {
+                    Token::TagOpen if skip_depth == 0 => {
+                        self.lexer.push_back(token);
+                        break;
+                    }
+                    Token::GameResult(_) if skip_depth == 0 => break,
+                    Token::VariationOpen => skip_depth += 1,
+                    Token::VariationClose => skip_depth = skip_depth.saturating_sub(1),
+                    _ => ()
+                }
+                continue;
+            }
+
+            match token {
+                Token::TagOpen => {
+                    // next game started without a result
+                    self.lexer.push_back(token);
+                    break;
+                }
+                Token::GameResult(token_result) => {
+                    if lines.len() > 1 {
+                        error = Some(self.error(PgnErrorKind::UnbalancedVariation));
+                    }
+                    result = Some(token_result);
+                    break;
+                }
+                Token::Symbol(symbol) => {
+                    // move numbers
+                    if symbol.bytes().all(|ch| ch.is_ascii_digit()) {
+                        continue;
+                    }
+
+                    let line = lines.last_mut().unwrap();
+                    match line.pos.parse_san(&symbol) {
+                        Ok(mv) => {
+                            let mut node = PgnNode::new(mv);
+                            if line.nodes.is_empty() {
+                                node.starting_comments = std::mem::take(&mut line.starting_comments);
+                            }
+                            line.nodes.push(node);
+
+                            line.prev_pos = line.pos;
+                            line.pos.make(&mv);
+                        }
+                        Err(err) => error = Some(self.error(PgnErrorKind::InvalidMove(err)))
+                    }
+                }
+                Token::Nag(nag) => {
+                    if let Some(node) = lines.last_mut().unwrap().nodes.last_mut() {
+                        node.nags.push(nag);
+                    }
+                }
+                Token::Comment(comment) => {
+                    let line = lines.last_mut().unwrap();
+                    match line.nodes.last_mut() {
+                        Some(node) => node.comments.push(comment),
+                        None => line.starting_comments.push(comment)
+                    }
+                }
+                Token::VariationOpen => {
+                    // a variation replaces the last move of the current line
+                    let line = lines.last().unwrap();
+                    if line.nodes.is_empty() {
+                        error = Some(self.error(PgnErrorKind::UnexpectedToken("(".to_string())));
+                        skip_depth = 1;
+                    } else {
+                        lines.push(Line::new(line.prev_pos));
+                    }
+                }
+                Token::VariationClose => {
+                    if lines.len() == 1 {
+                        error = Some(self.error(PgnErrorKind::UnbalancedVariation));
+                        continue;
+                    }
+
+                    let variation = lines.pop().unwrap();
+                    if !variation.nodes.is_empty() {
+                        lines.last_mut().unwrap().nodes.last_mut().unwrap().variations.push(variation.nodes);
+                    }
+                }
+                Token::TagClose => error = Some(self.error(PgnErrorKind::UnexpectedToken("]".to_string()))),
+                Token::Str(value) => error = Some(self.error(PgnErrorKind::UnexpectedToken(format!("\"{}\"", value))))
+            }
+        }
+
+        if error.is_none() && lines.len() > 1 {
+            error = Some(self.error(PgnErrorKind::UnbalancedVariation));
+        }
+
+        // only comments left at the end of the input
+        if error.is_none() && tags.is_empty() && result.is_none() && lines[0].nodes.is_empty() {
+            return None;
+        }
+
+        self.game_index += 1;
+        if let Some(err) = error {
+            return Some(Err(err));
+        }
+
+        let result = result
+            .or_else(|| tags.iter().find(|(name, _)| name == "Result").map(|(_, value)| value.clone()))
+            .unwrap_or_else(|| "*".to_string());
+
+        Some(Ok(PgnGame {
+            tags,
+            start,
+            moves: lines.pop().unwrap().nodes,
+            result,
+        }))
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<PgnGame, PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_game()
+    }
+}
@@ -0,0 +1,143 @@
+use std::fmt;
+
+use crate::types::*;
+use crate::position::Position;
+use super::{PgnGame, PgnNode};
+
+// the PGN export format limits lines to 80 columns
+const MAX_LINE_LENGTH: usize = 80;
+
+// tags every exported game starts with, in this order, and their values when unknown
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+
+impl PgnGame {
+    // write the game in PGN export format
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+
+        // tag pair section. the result tag always matches the movetext
+        for (name, default) in SEVEN_TAG_ROSTER {
+            let value: &str = if name == "Result" {&self.result} else {self.tag(name).unwrap_or(default)};
+            pgn.push_str(&tag_pair(name, value));
+        }
+        if self.start.chess960 {
+            pgn.push_str(&tag_pair("Variant", "Chess960"));
+        }
+        if self.start != Position::new() {
+            pgn.push_str(&tag_pair("SetUp", "1"));
+            pgn.push_str(&tag_pair("FEN", &self.start.to_fen()));
+        }
+        for (name, value) in self.tags.iter() {
+            let is_roster_tag = SEVEN_TAG_ROSTER.iter().any(|(roster_name, _)| roster_name == name);
+            if !is_roster_tag && name != "SetUp" && name != "FEN" && !(name == "Variant" && self.start.chess960) {
+                pgn.push_str(&tag_pair(name, value));
+            }
+        }
+        pgn.push('\n');
+
+        // movetext
+        let mut tokens: Vec<String> = Vec::new();
+        write_line(&mut tokens, &self.start, &self.moves);
+        tokens.push(self.result.clone());
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > MAX_LINE_LENGTH {
+                pgn.push_str(&line);
+                pgn.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        pgn.push_str(&line);
+        pgn.push('\n');
+
+        pgn
+    }
+}
+
+impl fmt::Display for PgnGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_pgn())
+    }
+}
+
+fn tag_pair(name: &str, value: &str) -> String {
+    format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// comments are split into words so long comments can wrap
+fn push_comment(tokens: &mut Vec<String>, comment: &str) {
+    let words: Vec<&str> = comment.split_whitespace().collect();
+    if words.is_empty() {
+        tokens.push("{}".to_string());
+        return;
+    }
+
+    let last = words.len() - 1;
+    for (i, word) in words.iter().enumerate() {
+        let mut token = word.to_string();
+        if i == 0 {
+            token.insert(0, '{');
+        }
+        if i == last {
+            token.push('}');
+        }
+        tokens.push(token);
+    }
+}
+
+// append the movetext tokens for a line of moves starting at pos
+fn write_line(tokens: &mut Vec<String>, pos: &Position, nodes: &[PgnNode]) {
+    let mut pos: Position = *pos;
+
+    // black moves need a number at the start of a line and after comments and variations
+    let mut needs_number = true;
+
+    for node in nodes.iter() {
+        for comment in node.starting_comments.iter() {
+            push_comment(tokens, comment);
+            needs_number = true;
+        }
+
+        if pos.turn == Color::White {
+            tokens.push(format!("{}.", pos.fullmove_count));
+        } else if needs_number {
+            tokens.push(format!("{}...", pos.fullmove_count));
+        }
+        tokens.push(pos.san(&node.mv));
+        needs_number = false;
+
+        for nag in node.nags.iter() {
+            tokens.push(format!("${}", nag));
+        }
+
+        for comment in node.comments.iter() {
+            push_comment(tokens, comment);
+            needs_number = true;
+        }
+
+        for variation in node.variations.iter() {
+            let start = tokens.len();
+            write_line(tokens, &pos, variation);
+            if tokens.len() > start {
+                tokens[start].insert(0, '(');
+                tokens.last_mut().unwrap().push(')');
+            }
+            needs_number = true;
+        }
+
+        pos.make(&node.mv);
+    }
+}
@@ -2,8 +2,11 @@ use std::fmt;
 
 use crate::types::*;
 use crate::bitboard::*;
+use crate::fen::STARTING_FEN;
+use crate::movegen::attack_tables::ATTACK_TABLE;
+use crate::zobrist::ZOBRIST;
 
-#[derive(Default, Copy, Clone)]
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
 pub struct Position {
     pub pawns: Bitboard,
     pub knights: Bitboard,
@@ -14,40 +17,87 @@ pub struct Position {
 
     pub occupied: [Bitboard; 2],
 
+    // squares of the rooks that can still castle
     pub castling_rights: Bitboard,
 
-    pub ep_square: Square,
+    pub ep_square: Option<Square>,
 
     pub turn: Color,
 
+    // enemy pieces giving check, own pieces pinned to the king, and the squares from the king (exclusive) to each
+    // pinning piece (inclusive). all for the side to move, and kept up to date by make and unmake
+    pub checkers: Bitboard,
+    pub pinned: Bitboard,
+    pub pin_rays: Bitboard,
+
+    // zobrist key, updated incrementally by make
+    pub hash: u64,
+
     pub halfmove_count: u8,
-    pub fullmove_count: u8,
+    pub fullmove_count: u16,
+
+    // chess960 rules for notation. castling works the same either way
+    pub chess960: bool,
 }
 
 impl Position {
     // create new standard chess starting position
     pub fn new() -> Self {
-        let mut pos: Position = Self {
-            pawns: BB_NONE,
-            knights: BB_NONE,
-            bishops: BB_NONE,
-            rooks: BB_NONE,
-            queens: BB_NONE,
-            kings: BB_NONE,
+        Position::from_fen(STARTING_FEN).unwrap()
+    }
 
-            occupied: [BB_NONE; 2],
+    // create one of the 960 chess960 starting positions from its Scharnagl number. 518 is the standard setup
+    pub fn from_chess960(scharnagl: u16) -> Option<Self> {
+        if scharnagl >= 960 {
+            return None;
+        }
 
-            castling_rights: BB_NONE,
-            ep_square: square::NONE,
+        let mut back_rank: [Option<Piece>; 8] = [None; 8];
+        let mut n: usize = scharnagl as usize;
 
-            turn: color::WHITE,
+        // bishops on opposite colors
+        back_rank[2 * (n % 4) + 1] = Some(Piece::Bishop);
+        n /= 4;
+        back_rank[2 * (n % 4)] = Some(Piece::Bishop);
+        n /= 4;
 
-            halfmove_count: 0,
-            fullmove_count: 0,
+        // queen and knights go on the remaining empty squares
+        let empty_file = |back_rank: &[Option<Piece>; 8], index: usize| -> usize {
+            (0..8).filter(|&file| back_rank[file].is_none()).nth(index).unwrap()
         };
 
-        pos.parse_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
-        pos
+        let queen_file = empty_file(&back_rank, n % 6);
+        back_rank[queen_file] = Some(Piece::Queen);
+        n /= 6;
+
+        const KNIGHT_PLACEMENTS: [(usize, usize); 10] = [(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)];
+        let (first, second) = KNIGHT_PLACEMENTS[n];
+        let knight_files = (empty_file(&back_rank, first), empty_file(&back_rank, second));
+        back_rank[knight_files.0] = Some(Piece::Knight);
+        back_rank[knight_files.1] = Some(Piece::Knight);
+
+        // the king goes between the rooks
+        for pt in [Piece::Rook, Piece::King, Piece::Rook] {
+            let file = empty_file(&back_rank, 0);
+            back_rank[file] = Some(pt);
+        }
+
+        let mut pos = Position::default();
+        pos.clear();
+        for (file, pt) in (0..8).zip(back_rank.map(Option::unwrap)) {
+            pos.set_piece_at(Square::from_coords_unchecked(file, 0), pt, Color::White);
+            pos.set_piece_at(Square::from_coords_unchecked(file, 1), Piece::Pawn, Color::White);
+            pos.set_piece_at(Square::from_coords_unchecked(file, 6), Piece::Pawn, Color::Black);
+            pos.set_piece_at(Square::from_coords_unchecked(file, 7), pt, Color::Black);
+        }
+
+        pos.castling_rights = pos.rooks;
+        pos.fullmove_count = 1;
+        pos.chess960 = true;
+        pos.hash = pos.compute_hash_from_scratch();
+        pos.update_check_info();
+
+        Some(pos)
     }
 
     pub fn clear(&mut self) {
@@ -61,269 +111,264 @@ impl Position {
         self.occupied = [BB_NONE; 2];
 
         self.castling_rights = BB_NONE;
-        self.ep_square = square::NONE;
+        self.ep_square = None;
+
+        self.turn = Color::White;
 
-        self.turn = color::WHITE;
+        self.checkers = BB_NONE;
+        self.pinned = BB_NONE;
+        self.pin_rays = BB_NONE;
+
+        self.hash = 0;
 
         self.halfmove_count = 0;
         self.fullmove_count = 0;
+
+        self.chess960 = false;
     }
 
-    // returns the piece at a given square
+    // returns the piece at a given square, if there is one
     #[inline]
-    pub fn piece_at(&self, sq: Square) -> Piece {
+    pub fn piece_at(&self, sq: Square) -> Option<Piece> {
         let mask: Bitboard = square_bb(sq);
 
         if mask & self.pawns > 0 {
-            return piece::PAWN
+            return Some(Piece::Pawn)
         }
         if mask & self.knights > 0 {
-            return piece::KNIGHT
+            return Some(Piece::Knight)
         }
         if mask & self.bishops > 0 {
-            return piece::BISHOP
+            return Some(Piece::Bishop)
         }
         if mask & self.rooks > 0 {
-            return piece::ROOK
+            return Some(Piece::Rook)
         }
         if mask & self.queens > 0 {
-            return piece::QUEEN
+            return Some(Piece::Queen)
         }
         if mask & self.kings > 0 {
-            return piece::KING
+            return Some(Piece::King)
         }
 
-        return piece::NONE
+        None
     }
 
-    // returns the color at a given square
+    // returns the color of the piece at a given square, if there is one
     #[inline]
-    pub fn color_at(&self, sq: Square) -> Color {
+    pub fn color_at(&self, sq: Square) -> Option<Color> {
         let mask: Bitboard = square_bb(sq);
 
-        if mask & self.occupied[color::WHITE] > 0 {
-            return color::WHITE
+        if mask & self.occupied[Color::White.index()] > 0 {
+            return Some(Color::White)
         }
-        if mask & self.occupied[color::BLACK] > 0 {
-            return color::BLACK
+        if mask & self.occupied[Color::Black.index()] > 0 {
+            return Some(Color::Black)
         }
-        return color::NONE
+        None
     }
+}
 
-    pub fn parse_fen(&mut self, fen: &str) {
-        let mut fen_data = fen.split(" ");
-        let rank_data: Vec<&str> = fen_data.next().unwrap().split("/").collect();
-        self.clear();
-        
-        // parse piece positions
-        let mut sq: Square = 0;
-        for rank in rank_data.iter().rev() {
-            for ch in rank.chars() {
-                if ch.is_ascii_uppercase(){
-                    self.occupied[color::WHITE] |= square_bb(sq);
-                } else if ch.is_ascii_lowercase() {
-                    self.occupied[color::BLACK] |= square_bb(sq);
-                }
+// state that make can't recover from the move alone, returned so the move can be unmade
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Undo {
+    pub captured: Option<Piece>,
+    pub castling_rights: Bitboard,
+    pub ep_square: Option<Square>,
+    pub halfmove_count: u8,
+    pub hash: u64,
+    pub checkers: Bitboard,
+    pub pinned: Bitboard,
+    pub pin_rays: Bitboard,
+}
 
-                match ch {
-                    '1'..='8' => {
-                        sq += ch as Square - '0' as Square;
-                    },
-                    'p'|'P' => {
-                        self.pawns |= square_bb(sq);
-                        sq += 1;
-                    },
-                    'n'|'N' => {
-                        self.knights |= square_bb(sq);
-                        sq += 1;
-                    },
-                    'b'|'B' => {
-                        self.bishops |= square_bb(sq);
-                        sq += 1;
-                    },
-                    'r'|'R' => {
-                        self.rooks |= square_bb(sq);
-                        sq += 1;
-                    },
-                    'q'|'Q' => {
-                        self.queens |= square_bb(sq);
-                        sq += 1;
-                    },
-                    'k'|'K' => {
-                        self.kings |= square_bb(sq);
-                        sq += 1;
-                    },
-                    _ => ()
-                }
-            }
-        }
-        assert_eq!(sq, 64);
+// implement move making features
+impl Position {
+    pub fn make(&mut self, mv: &Move) -> Undo {
+        let mut undo = Undo {
+            captured: None,
+            castling_rights: self.castling_rights,
+            ep_square: self.ep_square,
+            halfmove_count: self.halfmove_count,
+            hash: self.hash,
+            checkers: self.checkers,
+            pinned: self.pinned,
+            pin_rays: self.pin_rays,
+        };
 
-        // set turn (if it does not exist assume white to play and no castling rights/ep square)
-        match fen_data.next() {
-            Some("w") => self.turn = color::WHITE,
-            Some("b") => self.turn = color::BLACK,
-            _ => return
+        // increment move counters
+        self.halfmove_count = self.halfmove_count.saturating_add(1);
+        if self.turn == Color::Black {
+            self.fullmove_count += 1;
         }
 
-        // set castling rights
-        for ch in fen_data.next().unwrap().chars() {
-            match ch {
-                '-' => (),
-                'K' => self.castling_rights |= square_bb(square::H1),
-                'Q' => self.castling_rights |= square_bb(square::A1),
-                'k' => self.castling_rights |= square_bb(square::H8),
-                'q' => self.castling_rights |= square_bb(square::A8),
-                _ => return
-            }
-        }
+        let from_square: Square = mv.from_square();
+        let to_square: Square = mv.to_square();
+        let piece_type: Piece = self.remove_piece_at(from_square).expect("no piece to move");
 
-        // set en passant square
-        match fen_data.next() {
-            Some("-") => (),
-            Some(sq_str) => {
-                let sq_pos: Vec<char> = sq_str.chars().collect();
-                self.ep_square = 8 * (sq_pos[1] as Square - '1' as Square) + (sq_pos[0] as Square - 'a' as Square);
-            },
-            _ => ()
+        // castling rights are lost when the king moves, or when a rook moves or is captured
+        let prev_castling_rights: Bitboard = self.castling_rights;
+        self.castling_rights &= !(square_bb(from_square) | square_bb(to_square));
+        if piece_type == Piece::King {
+            self.castling_rights &= if self.turn == Color::White {!BB_RANK_1} else {!BB_RANK_8};
         }
-
-        // set halfmove count
-        match fen_data.next() {
-            Some(halfmove_str) => self.halfmove_count = halfmove_str.parse::<u8>().unwrap_or(0),
-            _ => ()
+        self.hash ^= ZOBRIST.castling(prev_castling_rights ^ self.castling_rights);
+
+        let prev_ep_square: Option<Square> = self.ep_square;
+        self.ep_square = None;
+
+        match mv.kind() {
+            MoveKind::Normal => {
+                undo.captured = self.remove_piece_at(to_square);
+                self.put_piece_at(to_square, piece_type, self.turn);
+
+                // double pawn pushes leave the skipped square open to en passant, if an enemy pawn could take there
+                if piece_type == Piece::Pawn && from_square.index() ^ to_square.index() == 16 {
+                    let ep_square: Square = from_square.offset_unchecked(if self.turn == Color::White {8} else {-8});
+                    if self.ep_square_capturable(ep_square, !self.turn) {
+                        self.ep_square = Some(ep_square);
+                    }
+                }
+            }
+            MoveKind::Promotion => {
+                undo.captured = self.remove_piece_at(to_square);
+                self.put_piece_at(to_square, mv.promotion_piece(), self.turn);
+            }
+            MoveKind::EnPassant => {
+                undo.captured = self.remove_piece_at(ep_capture_square(to_square, self.turn));
+                self.put_piece_at(to_square, Piece::Pawn, self.turn);
+            }
+            MoveKind::Castle => {
+                let (king_to, rook_to): (Square, Square) = castling_targets(from_square, to_square);
+                self.remove_piece_at(to_square);
+                self.put_piece_at(king_to, Piece::King, self.turn);
+                self.put_piece_at(rook_to, Piece::Rook, self.turn);
+            }
         }
 
-         // set fullmove count
-         match fen_data.next() {
-            Some(fullmove_str) => self.fullmove_count = fullmove_str.parse::<u8>().unwrap_or(0),
-            _ => ()
+        // pawn moves and captures zero the halfmove clock
+        if piece_type == Piece::Pawn || undo.captured.is_some() {
+            self.halfmove_count = 0;
         }
 
-    }
-}
+        self.hash ^= ZOBRIST.ep(prev_ep_square) ^ ZOBRIST.ep(self.ep_square);
 
-// implement move making features
-impl Position {
-    pub fn make(&mut self, mv: &Move) {
-        // increment move counters
-        self.halfmove_count += 1;
-        if self.turn == color::BLACK {
-            self.fullmove_count += 1;
-        }
+        self.turn = !self.turn;
+        self.hash ^= ZOBRIST.turn();
+        self.update_check_info();
 
-        // TODO: implement halfmove clock zeroing
+        debug_assert_eq!(self.hash, self.compute_hash_from_scratch());
 
-        let from_bb: Bitboard = square_bb(mv.from_square);
-        let to_bb: Bitboard = square_bb(mv.to_square);
-
-        let mut piece_type: Piece = self.remove_piece_at(mv.from_square);
+        undo
+    }
 
-        // castling rights
-        self.castling_rights &= !(from_bb | to_bb);
-        if piece_type == piece::KING {
-            // update castling rights on king move
-            if self.turn == color::WHITE {
-                self.castling_rights &= !BB_RANK_1;
-            } else {
-                self.castling_rights &= !BB_RANK_8;
-            }
+    // take back a move made with make, restoring the exact previous state
+    pub fn unmake(&mut self, mv: &Move, undo: &Undo) {
+        self.turn = !self.turn;
+        if self.turn == Color::Black {
+            self.fullmove_count -= 1;
         }
 
-        // en passant related things
-        let prev_ep_square: Square = self.ep_square;
-        self.ep_square = square::NONE;
-
-        if piece_type == piece::PAWN {
-            let delta: i8 = mv.to_square as i8 - mv.from_square as i8;
+        let from_square: Square = mv.from_square();
+        let to_square: Square = mv.to_square();
 
-            if delta == 16 {
-                self.ep_square = mv.from_square + 8;
-            } else if delta == -16 {
-                self.ep_square = mv.from_square - 8;
-            } else if mv.to_square == prev_ep_square {
-                self.remove_piece_at((prev_ep_square as i8 + if self.turn == color::WHITE {-8} else {8}) as Square);
+        match mv.kind() {
+            MoveKind::Normal => {
+                let moved_piece: Piece = self.remove_piece_at(to_square).expect("no piece to unmake");
+                self.put_piece_at(from_square, moved_piece, self.turn);
+                if let Some(captured) = undo.captured {
+                    self.put_piece_at(to_square, captured, !self.turn);
+                }
             }
-        }
-
-        // handle pawn promotions
-        if mv.promotion != piece::NONE {
-            piece_type = mv.promotion;
-        }
-
-        // add new piece, also handle castling
-        if piece_type == piece::KING && chebyshev_distance(mv.from_square, mv.to_square) > 1 {
-            if square_file(mv.to_square) < square_file(mv.from_square) {
-                self.set_piece_at(if self.turn == color::WHITE {square::C1} else {square::C8}, piece::KING, self.turn);
-                self.set_piece_at(if self.turn == color::WHITE {square::D1} else {square::D8}, piece::ROOK, self.turn);
-                self.remove_piece_at(if self.turn == color::WHITE {square::A1} else {square::A8});
-            } else {
-                self.set_piece_at(if self.turn == color::WHITE {square::G1} else {square::G8}, piece::KING, self.turn);
-                self.set_piece_at(if self.turn == color::WHITE {square::F1} else {square::F8}, piece::ROOK, self.turn);
-                self.remove_piece_at(if self.turn == color::WHITE {square::H1} else {square::H8});
+            MoveKind::Promotion => {
+                self.remove_piece_at(to_square);
+                self.put_piece_at(from_square, Piece::Pawn, self.turn);
+                if let Some(captured) = undo.captured {
+                    self.put_piece_at(to_square, captured, !self.turn);
+                }
+            }
+            MoveKind::EnPassant => {
+                self.remove_piece_at(to_square);
+                self.put_piece_at(from_square, Piece::Pawn, self.turn);
+                self.put_piece_at(ep_capture_square(to_square, self.turn), Piece::Pawn, !self.turn);
+            }
+            MoveKind::Castle => {
+                let (king_to, rook_to): (Square, Square) = castling_targets(from_square, to_square);
+                self.remove_piece_at(king_to);
+                self.remove_piece_at(rook_to);
+                self.put_piece_at(from_square, Piece::King, self.turn);
+                self.put_piece_at(to_square, Piece::Rook, self.turn);
             }
-        } else {
-            self.set_piece_at(mv.to_square, piece_type, self.turn);
         }
 
-
-        self.turn ^= 1;
+        self.castling_rights = undo.castling_rights;
+        self.ep_square = undo.ep_square;
+        self.halfmove_count = undo.halfmove_count;
+        self.hash = undo.hash;
+        self.checkers = undo.checkers;
+        self.pinned = undo.pinned;
+        self.pin_rays = undo.pin_rays;
     }
 
-    // remove piece (assuming it's already there)
-    fn remove_piece_at(&mut self, sq: Square) -> Piece {
-        let piece_type: Piece = self.piece_at(sq);
-        let piece_color: Color = self.color_at(sq);
+    // remove the piece on a square, returning what was there
+    pub(crate) fn remove_piece_at(&mut self, sq: Square) -> Option<Piece> {
+        let piece_type: Piece = self.piece_at(sq)?;
+        let piece_color: Color = if self.occupied[Color::White.index()] & square_bb(sq) > 0 {Color::White} else {Color::Black};
         let piece_bb: Bitboard = !square_bb(sq);
 
         match piece_type {
-            piece::PAWN => self.pawns &= piece_bb,
-            piece::KNIGHT => self.knights &= piece_bb,
-            piece::BISHOP => self.bishops &= piece_bb,
-            piece::ROOK => self.rooks &= piece_bb,
-            piece::QUEEN => self.queens &= piece_bb,
-            piece::KING => self.kings &= piece_bb,
-            piece::NONE => return piece_type,
-            _ => ()
+            Piece::Pawn => self.pawns &= piece_bb,
+            Piece::Knight => self.knights &= piece_bb,
+            Piece::Bishop => self.bishops &= piece_bb,
+            Piece::Rook => self.rooks &= piece_bb,
+            Piece::Queen => self.queens &= piece_bb,
+            Piece::King => self.kings &= piece_bb
         }
 
-        self.occupied[piece_color] &= piece_bb;
+        self.occupied[piece_color.index()] &= piece_bb;
+        self.hash ^= ZOBRIST.piece(sq, piece_type, piece_color);
 
-        piece_type
+        Some(piece_type)
     }
 
-    fn set_piece_at(&mut self, sq: Square, piece_type: Piece, piece_color: Color) {
+    // whether a pawn of co could capture en passant onto ep_square. an ep square nobody can take on leaves the
+    // position the same as without one, so it is never set and can't split the hashes of repeated positions
+    #[inline]
+    pub(crate) fn ep_square_capturable(&self, ep_square: Square, co: Color) -> bool {
+        ATTACK_TABLE.get_pawn_attacks(ep_square, !co) & self.pawns & self.occupied[co.index()] > 0
+    }
+
+    // place a piece, replacing whatever was on the square
+    pub(crate) fn set_piece_at(&mut self, sq: Square, piece_type: Piece, piece_color: Color) {
         self.remove_piece_at(sq);
+        self.put_piece_at(sq, piece_type, piece_color);
+    }
 
+    // place a piece on a square known to be empty
+    #[inline]
+    fn put_piece_at(&mut self, sq: Square, piece_type: Piece, piece_color: Color) {
         let piece_bb: Bitboard = square_bb(sq);
         match piece_type {
-            piece::PAWN => self.pawns |= piece_bb,
-            piece::KNIGHT => self.knights |= piece_bb,
-            piece::BISHOP => self.bishops |= piece_bb,
-            piece::ROOK => self.rooks |= piece_bb,
-            piece::QUEEN => self.queens |= piece_bb,
-            piece::KING => self.kings |= piece_bb,
-            _ => ()
+            Piece::Pawn => self.pawns |= piece_bb,
+            Piece::Knight => self.knights |= piece_bb,
+            Piece::Bishop => self.bishops |= piece_bb,
+            Piece::Rook => self.rooks |= piece_bb,
+            Piece::Queen => self.queens |= piece_bb,
+            Piece::King => self.kings |= piece_bb
         }
 
-        self.occupied[piece_color] |= piece_bb;
+        self.occupied[piece_color.index()] |= piece_bb;
+        self.hash ^= ZOBRIST.piece(sq, piece_type, piece_color);
     }
 }
 
 impl fmt::Display for Position {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for i in (0..8).rev() {
-            for j in 0..8 {
-                let mut piece_char: char = match self.piece_at(i*8 + j) {
-                    piece::PAWN => 'P',
-                    piece::KNIGHT => 'N',
-                    piece::BISHOP => 'B',
-                    piece::ROOK => 'R',
-                    piece::QUEEN => 'Q',
-                    piece::KING => 'K',
-                    _ => '.'
-                };
-                if self.color_at(i*8 + j) == color::BLACK{
+        for rank in (0..8).rev() {
+            for file in 0..8 {
+                let sq: Square = Square::from_coords_unchecked(file, rank);
+                let mut piece_char: char = self.piece_at(sq).map_or('.', Piece::symbol);
+                if self.color_at(sq) == Some(Color::Black) {
                     piece_char = piece_char.to_ascii_lowercase();
                 }
 
@@ -331,7 +376,51 @@ impl fmt::Display for Position {
             }
             writeln!(f)?;
         }
-        writeln!(f, "\n{} to play\n", if self.turn == color::WHITE {"White"} else {"Black"})?;
+        writeln!(f, "\n{} to play", if self.turn == Color::White {"White"} else {"Black"})?;
+        writeln!(f, "Castling rights: {}", self.castling_fen())?;
+        writeln!(f, "En passant: {}", self.ep_fen())?;
+        writeln!(f, "Halfmove clock: {}", self.halfmove_count)?;
+        writeln!(f, "Fullmove number: {}\n", self.fullmove_count)?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+// square of the pawn taken by an en passant capture onto ep_square by co
+#[inline]
+pub(crate) fn ep_capture_square(ep_square: Square, co: Color) -> Square {
+    ep_square.offset_unchecked(if co == Color::White {-8} else {8})
+}
+
+// back ranks of known Scharnagl numbers, from https://en.wikipedia.org/wiki/Fischer_random_chess_numbering_scheme
+pub const CHESS960_RESULTS: [(u16, &str); 6] = [
+    (0, "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1"),
+    (1, "bqnbnrkr/pppppppp/8/8/8/8/PPPPPPPP/BQNBNRKR w KQkq - 0 1"),
+    (2, "bqnnrbkr/pppppppp/8/8/8/8/PPPPPPPP/BQNNRBKR w KQkq - 0 1"),
+    (4, "qbbnnrkr/pppppppp/8/8/8/8/PPPPPPPP/QBBNNRKR w KQkq - 0 1"),
+    (518, STARTING_FEN),
+    (959, "rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB w KQkq - 0 1")
+];
+
+pub fn check_chess960_correctness() {
+    for (scharnagl, fen) in CHESS960_RESULTS {
+        let pos = Position::from_chess960(scharnagl).unwrap();
+        assert_eq!(pos.to_fen(), fen, "Scharnagl number {}", scharnagl);
+        assert_eq!(Position::from_fen(fen).map(|parsed| parsed.board_fen()), Ok(pos.board_fen()));
+    }
+
+    // the standard setup is the standard position apart from the chess960 flag
+    assert_eq!(Position { chess960: false, ..Position::from_chess960(518).unwrap() }, Position::new());
+
+    // every number gives a different valid position, and there are no more than 960
+    let mut boards: Vec<String> = (0..960).map(|scharnagl| {
+        let pos = Position::from_chess960(scharnagl).unwrap();
+        assert!(pos.is_valid(), "Scharnagl number {} is invalid", scharnagl);
+        pos.board_fen()
+    }).collect();
+    boards.sort();
+    boards.dedup();
+    assert_eq!(boards.len(), 960);
+    assert_eq!(Position::from_chess960(960), None);
+
+    println!("Chess960 passed");
+}
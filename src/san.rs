@@ -0,0 +1,187 @@
+use std::fmt;
+
+use crate::types::*;
+use crate::move_list::MoveList;
+use crate::position::Position;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SanError {
+    // not recognizable as a move in standard algebraic notation
+    Invalid(String),
+    // no legal move matches
+    Illegal(String),
+    // more than one legal move matches
+    Ambiguous(String),
+}
+
+impl fmt::Display for SanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SanError::Invalid(san) => write!(f, "invalid SAN move '{}'", san),
+            SanError::Illegal(san) => write!(f, "illegal SAN move '{}'", san),
+            SanError::Ambiguous(san) => write!(f, "ambiguous SAN move '{}'", san)
+        }
+    }
+}
+
+impl std::error::Error for SanError {}
+
+impl Position {
+    // write a legal move in standard algebraic notation, e.g. Nbd7, exd6, O-O-O, e8=Q+ or Qxf7#
+    pub fn san(&self, mv: &Move) -> String {
+        let mut san = String::new();
+        let piece_type: Piece = self.piece_at(mv.from_square()).expect("no piece to move");
+
+        if mv.is_castle() {
+            san.push_str(if mv.to_square().file() < mv.from_square().file() {"O-O-O"} else {"O-O"});
+        } else {
+            let is_capture: bool = self.piece_at(mv.to_square()).is_some() || mv.is_en_passant();
+
+            if piece_type == Piece::Pawn {
+                if is_capture {
+                    san.push((b'a' + mv.from_square().file()) as char);
+                }
+            } else {
+                san.push(piece_type.symbol());
+                san.push_str(&self.san_disambiguation(mv, piece_type));
+            }
+
+            if is_capture {
+                san.push('x');
+            }
+            san.push_str(mv.to_square().name());
+
+            if let Some(promotion) = mv.promotion() {
+                san.push('=');
+                san.push(promotion.symbol());
+            }
+        }
+
+        // check and mate suffixes
+        if self.gives_check(mv) {
+            let mut updated_pos = *self;
+            updated_pos.make(mv);
+            san.push(if updated_pos.has_legal_moves() {'+'} else {'#'});
+        }
+
+        san
+    }
+
+    // file and/or rank of the moving piece, if another piece of the same type could also move to the target square
+    fn san_disambiguation(&self, mv: &Move, piece_type: Piece) -> String {
+        let mut ambiguous = false;
+        let mut same_file = false;
+        let mut same_rank = false;
+        for other in self.legal_moves().iter() {
+            if other.to_square() != mv.to_square() || other.from_square() == mv.from_square() || self.piece_at(other.from_square()) != Some(piece_type) {
+                continue;
+            }
+
+            ambiguous = true;
+            same_file |= other.from_square().file() == mv.from_square().file();
+            same_rank |= other.from_square().rank() == mv.from_square().rank();
+        }
+
+        let from_name: &str = mv.from_square().name();
+        if !ambiguous {
+            String::new()
+        } else if !same_file {
+            from_name[0..1].to_string()
+        } else if !same_rank {
+            from_name[1..2].to_string()
+        } else {
+            from_name.to_string()
+        }
+    }
+
+    // parse a move in standard algebraic notation. also accepts common loose forms such as a missing capture sign,
+    // castling written with zeros, missing or extra check suffixes and promotions written without '='
+    pub fn parse_san(&self, san: &str) -> Result<Move, SanError> {
+        let invalid = || SanError::Invalid(san.to_string());
+
+        // strip check, mate and annotation suffixes
+        let trimmed: &str = san.trim().trim_end_matches(['+', '#', '!', '?']);
+        if trimmed.is_empty() || !trimmed.is_ascii() {
+            return Err(invalid());
+        }
+
+        let legal_moves: MoveList = self.legal_moves();
+
+        // castling
+        let castling: String = trimmed.replace('0', "O");
+        if castling == "O-O" || castling == "O-O-O" {
+            let queenside: bool = castling == "O-O-O";
+            return legal_moves.iter()
+                .find(|mv| {
+                    mv.is_castle() &&
+                    (mv.to_square().file() < mv.from_square().file()) == queenside
+                })
+                .copied()
+                .ok_or_else(|| SanError::Illegal(san.to_string()));
+        }
+
+        let mut chars: &[u8] = trimmed.as_bytes();
+
+        // moving piece
+        let piece_type: Piece = match chars[0] {
+            b'N' => Piece::Knight,
+            b'B' => Piece::Bishop,
+            b'R' => Piece::Rook,
+            b'Q' => Piece::Queen,
+            b'K' => Piece::King,
+            _ => Piece::Pawn
+        };
+        if piece_type != Piece::Pawn {
+            chars = &chars[1..];
+        }
+
+        // promotion, with or without '='
+        let mut promotion: Option<Piece> = None;
+        if piece_type == Piece::Pawn {
+            if let Some((&last, rest)) = chars.split_last() {
+                promotion = match last.to_ascii_uppercase() {
+                    b'N' => Some(Piece::Knight),
+                    b'B' => Some(Piece::Bishop),
+                    b'R' => Some(Piece::Rook),
+                    b'Q' => Some(Piece::Queen),
+                    _ => None
+                };
+                if promotion.is_some() {
+                    chars = rest.strip_suffix(b"=").unwrap_or(rest);
+                }
+            }
+        }
+
+        // target square
+        if chars.len() < 2 {
+            return Err(invalid());
+        }
+        let (rest, target) = chars.split_at(chars.len() - 2);
+        let to_square: Square = std::str::from_utf8(target).ok().and_then(|name| name.parse().ok()).ok_or_else(invalid)?;
+
+        // whatever is left is an optional file and rank of the moving piece, and an optional capture sign
+        let rest: &[u8] = rest.strip_suffix(b"x").or_else(|| rest.strip_suffix(b":")).unwrap_or(rest);
+        let (from_file, from_rank): (Option<u8>, Option<u8>) = match rest {
+            [] => (None, None),
+            [file @ b'a'..=b'h'] => (Some(file - b'a'), None),
+            [rank @ b'1'..=b'8'] => (None, Some(rank - b'1')),
+            [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => (Some(file - b'a'), Some(rank - b'1')),
+            _ => return Err(invalid())
+        };
+
+        let mut candidates = legal_moves.iter().filter(|mv| {
+            mv.to_square() == to_square &&
+            mv.promotion() == promotion &&
+            !mv.is_castle() &&
+            self.piece_at(mv.from_square()) == Some(piece_type) &&
+            from_file.is_none_or(|file| mv.from_square().file() == file) &&
+            from_rank.is_none_or(|rank| mv.from_square().rank() == rank)
+        });
+
+        match (candidates.next(), candidates.next()) {
+            (Some(mv), None) => Ok(*mv),
+            (Some(_), Some(_)) => Err(SanError::Ambiguous(san.to_string())),
+            (None, _) => Err(SanError::Illegal(san.to_string()))
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::types::*;
+use crate::bitboard::*;
+use crate::movegen::attack_tables::ATTACK_TABLE;
+use crate::position::{Position, ep_capture_square};
+
+// piece values used to score exchanges, indexed by Piece::index. the king is never actually given up, since it may
+// only capture when nothing can take it back
+pub const SEE_VALUES: [i32; 6] = [100, 300, 300, 500, 900, 20_000];
+
+impl Position {
+    // static exchange evaluation: material won by the side to move after the move and the best sequence of recaptures
+    // on the target square, with each side free to stop capturing. the least valuable attacker always captures next,
+    // sliders lined up behind the capturers join in, and a pawn reaching the last rank promotes to a queen. pins and
+    // checks are ignored. castling scores 0
+    pub fn see(&self, mv: &Move) -> i32 {
+        let from_square: Square = mv.from_square();
+        let to_square: Square = mv.to_square();
+        let mut occupied: Bitboard = (self.occupied[Color::White.index()] | self.occupied[Color::Black.index()]) & !square_bb(from_square);
+
+        // material won by the move itself, and the value of the piece left standing on the target square
+        let (mut gain, mut victim): (i32, i32) = match mv.kind() {
+            MoveKind::Normal => (self.piece_value_at(to_square), self.piece_value_at(from_square)),
+            MoveKind::Promotion => {
+                let promotion: i32 = SEE_VALUES[mv.promotion_piece().index()];
+                (self.piece_value_at(to_square) + promotion - SEE_VALUES[Piece::Pawn.index()], promotion)
+            }
+            MoveKind::EnPassant => {
+                occupied &= !square_bb(ep_capture_square(to_square, self.turn));
+                (SEE_VALUES[Piece::Pawn.index()], SEE_VALUES[Piece::Pawn.index()])
+            }
+            MoveKind::Castle => return 0
+        };
+
+        // speculative gains for the side making each capture, resolved backwards once nobody can capture any more
+        let mut gains: [i32; 32] = [0; 32];
+        gains[0] = gain;
+        let mut depth: usize = 0;
+
+        let mut attackers: Bitboard = self.attackers_to(to_square, Color::White, occupied) | self.attackers_to(to_square, Color::Black, occupied);
+        let mut side: Color = !self.turn;
+        loop {
+            let side_attackers: Bitboard = attackers & occupied & self.occupied[side.index()];
+            if side_attackers == 0 {
+                break;
+            }
+
+            let (attacker, attacker_square): (Piece, Square) = self.least_valuable(side_attackers);
+            occupied &= !square_bb(attacker_square);
+
+            // sliders behind the capturer can now see the target square
+            attackers |= (ATTACK_TABLE.get_sliding_attacks(to_square, Piece::Bishop, occupied) & (self.bishops | self.queens)) |
+                (ATTACK_TABLE.get_sliding_attacks(to_square, Piece::Rook, occupied) & (self.rooks | self.queens));
+
+            // the king can only capture if it can't be taken back
+            if attacker == Piece::King && attackers & occupied & self.occupied[(!side).index()] > 0 {
+                break;
+            }
+
+            gain = victim - gain;
+            victim = SEE_VALUES[attacker.index()];
+            if attacker == Piece::Pawn && (to_square.rank() == 0 || to_square.rank() == 7) {
+                gain += SEE_VALUES[Piece::Queen.index()] - SEE_VALUES[Piece::Pawn.index()];
+                victim = SEE_VALUES[Piece::Queen.index()];
+            }
+
+            depth += 1;
+            gains[depth] = gain;
+            side = !side;
+        }
+
+        // each side only makes a capture if it does better than stopping
+        while depth > 0 {
+            gains[depth - 1] = -(-gains[depth - 1]).max(gains[depth]);
+            depth -= 1;
+        }
+
+        gains[0]
+    }
+
+    // whether the static exchange evaluation of a move is at least threshold
+    pub fn see_ge(&self, mv: &Move, threshold: i32) -> bool {
+        self.see(mv) >= threshold
+    }
+
+    fn piece_value_at(&self, sq: Square) -> i32 {
+        self.piece_at(sq).map_or(0, |pt| SEE_VALUES[pt.index()])
+    }
+
+    // cheapest piece among the attackers, and its square
+    fn least_valuable(&self, attackers: Bitboard) -> (Piece, Square) {
+        for (pt, pieces) in [(Piece::Pawn, self.pawns), (Piece::Knight, self.knights), (Piece::Bishop, self.bishops),
+                             (Piece::Rook, self.rooks), (Piece::Queen, self.queens), (Piece::King, self.kings)] {
+            if attackers & pieces > 0 {
+                return (pt, lsb(attackers & pieces));
+            }
+        }
+        panic!("Attacker is not a piece");
+    }
+}
+
+pub struct SeeResult {
+    pub fen: &'static str,
+    pub san: &'static str,
+    pub see: i32
+}
+
+// exchanges with known outcomes, covering x-rays, king recaptures, promotions and en passant
+pub const SEE_RESULTS: [SeeResult; 12] = [
+    SeeResult {fen: "1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", san: "Rxe5", see: 100},
+    SeeResult {fen: "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1", san: "Nxe5", see: -200},
+    SeeResult {fen: "3rk3/8/8/3p4/8/8/8/3RK3 w - - 0 1", san: "Rxd5", see: -400},
+    SeeResult {fen: "3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", san: "Rxd5", see: 100},
+    SeeResult {fen: "3rk3/8/8/3P4/8/8/8/3RK3 b - - 0 1", san: "Rxd5", see: -400},
+    SeeResult {fen: "4k3/8/8/8/8/2p5/8/3RK3 w - - 0 1", san: "Rd2", see: -400},
+    SeeResult {fen: "3rk3/8/8/8/8/2p5/8/3RK3 w - - 0 1", san: "Rd2", see: -500},
+    SeeResult {fen: "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", san: "exd6", see: 100},
+    SeeResult {fen: "8/4k3/8/3pP3/8/8/8/4K3 w - d6 0 1", san: "exd6", see: 0},
+    SeeResult {fen: "4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", san: "b8=Q", see: 800},
+    SeeResult {fen: "r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1", san: "b8=Q", see: -100},
+    SeeResult {fen: "r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1", san: "bxa8=Q", see: 1300}
+];
+
+pub fn check_see_correctness() {
+    for see_res in SEE_RESULTS {
+        let pos = Position::from_fen(see_res.fen).unwrap();
+        let mv = pos.parse_san(see_res.san).unwrap();
+
+        assert_eq!(pos.see(&mv), see_res.see, "SEE of {} in '{}'", see_res.san, see_res.fen);
+        assert!(pos.see_ge(&mv, see_res.see) && !pos.see_ge(&mv, see_res.see + 1));
+        println!("SEE '{}' {} passed", see_res.fen, see_res.san);
+    }
+    println!("SEE passed");
+}
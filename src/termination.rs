@@ -0,0 +1,65 @@
+use crate::bitboard::*;
+use crate::position::Position;
+
+// ways a game ends automatically, without either player claiming anything
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    SeventyFiveMoves,
+    InsufficientMaterial,
+}
+
+impl Position {
+    // returns how the game has ended in this position, if it has. checkmate takes precedence over the move rules
+    pub fn termination(&self) -> Option<Termination> {
+        if !self.has_legal_moves() {
+            return Some(if self.is_check() {Termination::Checkmate} else {Termination::Stalemate});
+        }
+        if self.is_seventy_five_moves() {
+            return Some(Termination::SeventyFiveMoves);
+        }
+        if self.is_insufficient_material() {
+            return Some(Termination::InsufficientMaterial);
+        }
+        None
+    }
+
+    pub fn has_legal_moves(&self) -> bool {
+        !self.legal_moves().is_empty()
+    }
+
+    pub fn is_checkmate(&self) -> bool {
+        self.is_check() && !self.has_legal_moves()
+    }
+
+    pub fn is_stalemate(&self) -> bool {
+        !self.is_check() && !self.has_legal_moves()
+    }
+
+    // the side to move may claim a draw: 50 moves by each side without a pawn move or capture
+    #[inline]
+    pub fn is_fifty_moves(&self) -> bool {
+        self.halfmove_count >= 100
+    }
+
+    // the game is drawn automatically after 75 moves by each side without a pawn move or capture
+    #[inline]
+    pub fn is_seventy_five_moves(&self) -> bool {
+        self.halfmove_count >= 150
+    }
+
+    // neither side can possibly checkmate: K vs K, K and a single minor piece vs K,
+    // or kings and bishops where every bishop is on the same square color
+    pub fn is_insufficient_material(&self) -> bool {
+        if self.pawns | self.rooks | self.queens > 0 {
+            return false;
+        }
+
+        if self.knights > 0 {
+            return popcount(self.knights | self.bishops) == 1;
+        }
+
+        self.bishops & BB_LIGHT_SQUARES == 0 || self.bishops & BB_DARK_SQUARES == 0
+    }
+}
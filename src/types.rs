@@ -1,108 +1,421 @@
+use std::fmt;
+use std::ops::Not;
+use std::str::FromStr;
+
 pub type Bitboard = u64;
-pub type Piece = usize;
-pub type Color = usize;
-pub type Square = usize;
-
-#[derive(Default, Debug, Clone, Copy)]
-pub struct Move {
-    pub from_square: Square,
-    pub to_square: Square,
-    pub promotion: Piece
+
+#[repr(u8)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Color {
+    #[default]
+    White,
+    Black
+}
+
+impl Color {
+    pub const ALL: [Color; 2] = [Color::White, Color::Black];
+
+    // index into arrays with one entry per color
+    #[inline]
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    // the other side
+    #[inline]
+    pub const fn flip(self) -> Color {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White
+        }
+    }
+}
+
+impl Not for Color {
+    type Output = Color;
+
+    #[inline]
+    fn not(self) -> Color {
+        self.flip()
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", if *self == Color::White {"white"} else {"black"})
+    }
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Piece {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King
+}
+
+impl Piece {
+    pub const ALL: [Piece; 6] = [Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen, Piece::King];
+
+    // pieces a pawn can promote to, most valuable first
+    pub const PROMOTIONS: [Piece; 4] = [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight];
+
+    // index into arrays with one entry per piece type
+    #[inline]
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    // uppercase letter used in FEN and SAN, e.g. 'N' for knights
+    #[inline]
+    pub const fn symbol(self) -> char {
+        match self {
+            Piece::Pawn => 'P',
+            Piece::Knight => 'N',
+            Piece::Bishop => 'B',
+            Piece::Rook => 'R',
+            Piece::Queen => 'Q',
+            Piece::King => 'K'
+        }
+    }
+
+    // piece type for a FEN/SAN letter in either case
+    pub const fn from_symbol(ch: char) -> Option<Piece> {
+        match ch.to_ascii_uppercase() {
+            'P' => Some(Piece::Pawn),
+            'N' => Some(Piece::Knight),
+            'B' => Some(Piece::Bishop),
+            'R' => Some(Piece::Rook),
+            'Q' => Some(Piece::Queen),
+            'K' => Some(Piece::King),
+            _ => None
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Piece::Pawn => "Pawn",
+            Piece::Knight => "Knight",
+            Piece::Bishop => "Bishop",
+            Piece::Rook => "Rook",
+            Piece::Queen => "Queen",
+            Piece::King => "King"
+        }
+    }
+}
+
+// a square on the board, numbered a1 = 0, b1 = 1, ..., h8 = 63
+#[derive(Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Square(u8);
+
+impl Square {
+    pub const A1: Square = Square(0);
+    pub const B1: Square = Square(1);
+    pub const C1: Square = Square(2);
+    pub const D1: Square = Square(3);
+    pub const E1: Square = Square(4);
+    pub const F1: Square = Square(5);
+    pub const G1: Square = Square(6);
+    pub const H1: Square = Square(7);
+    pub const A2: Square = Square(8);
+    pub const B2: Square = Square(9);
+    pub const C2: Square = Square(10);
+    pub const D2: Square = Square(11);
+    pub const E2: Square = Square(12);
+    pub const F2: Square = Square(13);
+    pub const G2: Square = Square(14);
+    pub const H2: Square = Square(15);
+    pub const A3: Square = Square(16);
+    pub const B3: Square = Square(17);
+    pub const C3: Square = Square(18);
+    pub const D3: Square = Square(19);
+    pub const E3: Square = Square(20);
+    pub const F3: Square = Square(21);
+    pub const G3: Square = Square(22);
+    pub const H3: Square = Square(23);
+    pub const A4: Square = Square(24);
+    pub const B4: Square = Square(25);
+    pub const C4: Square = Square(26);
+    pub const D4: Square = Square(27);
+    pub const E4: Square = Square(28);
+    pub const F4: Square = Square(29);
+    pub const G4: Square = Square(30);
+    pub const H4: Square = Square(31);
+    pub const A5: Square = Square(32);
+    pub const B5: Square = Square(33);
+    pub const C5: Square = Square(34);
+    pub const D5: Square = Square(35);
+    pub const E5: Square = Square(36);
+    pub const F5: Square = Square(37);
+    pub const G5: Square = Square(38);
+    pub const H5: Square = Square(39);
+    pub const A6: Square = Square(40);
+    pub const B6: Square = Square(41);
+    pub const C6: Square = Square(42);
+    pub const D6: Square = Square(43);
+    pub const E6: Square = Square(44);
+    pub const F6: Square = Square(45);
+    pub const G6: Square = Square(46);
+    pub const H6: Square = Square(47);
+    pub const A7: Square = Square(48);
+    pub const B7: Square = Square(49);
+    pub const C7: Square = Square(50);
+    pub const D7: Square = Square(51);
+    pub const E7: Square = Square(52);
+    pub const F7: Square = Square(53);
+    pub const G7: Square = Square(54);
+    pub const H7: Square = Square(55);
+    pub const A8: Square = Square(56);
+    pub const B8: Square = Square(57);
+    pub const C8: Square = Square(58);
+    pub const D8: Square = Square(59);
+    pub const E8: Square = Square(60);
+    pub const F8: Square = Square(61);
+    pub const G8: Square = Square(62);
+    pub const H8: Square = Square(63);
+
+    // square from its index, if it is below 64
+    #[inline]
+    pub const fn new(index: u8) -> Option<Square> {
+        if index < 64 {Some(Square(index))} else {None}
+    }
+
+    // square from file and rank, both counted from 0, if both are below 8
+    #[inline]
+    pub const fn from_coords(file: u8, rank: u8) -> Option<Square> {
+        if file < 8 && rank < 8 {Some(Square(rank * 8 + file))} else {None}
+    }
+
+    // index into arrays with one entry per square
+    #[inline]
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    // 0 for the first rank up to 7 for the eighth
+    #[inline]
+    pub const fn rank(self) -> u8 {
+        self.0 >> 3
+    }
+
+    // 0 for the a-file up to 7 for the h-file
+    #[inline]
+    pub const fn file(self) -> u8 {
+        self.0 & 7
+    }
+
+    // the same square seen from the other side of the board, e.g. e2 <-> e7
+    #[inline]
+    pub const fn flip(self) -> Square {
+        Square(self.0 ^ 56)
+    }
+
+    // the square delta steps away in index order, if it is still on the board. doesn't check for wrapping around files
+    #[inline]
+    pub const fn offset(self, delta: i8) -> Option<Square> {
+        let index = self.0 as i8 + delta;
+        if index >= 0 && index < 64 {Some(Square(index as u8))} else {None}
+    }
+
+    // like offset, for move generation where the target is known to be on the board
+    #[inline]
+    pub(crate) const fn offset_unchecked(self, delta: i8) -> Square {
+        Square::new_unchecked((self.0 as i8 + delta) as u8)
+    }
+
+    // like new, for indices known to be below 64
+    #[inline]
+    pub(crate) const fn new_unchecked(index: u8) -> Square {
+        debug_assert!(index < 64);
+        Square(index)
+    }
+
+    // like from_coords, for a file and rank known to be below 8
+    #[inline]
+    pub(crate) const fn from_coords_unchecked(file: u8, rank: u8) -> Square {
+        debug_assert!(file < 8 && rank < 8);
+        Square(rank * 8 + file)
+    }
+
+    #[inline]
+    pub const fn name(self) -> &'static str {
+        crate::bitboard::SQUARE_NAMES[self.0 as usize]
+    }
+
+    // every square from a1 to h8
+    pub fn iter() -> impl DoubleEndedIterator<Item = Square> + ExactSizeIterator {
+        (0..64).map(Square)
+    }
 }
 
-impl std::fmt::Display for Move {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} to {}", crate::bitboard::SQUARE_NAMES[self.from_square as usize], crate::bitboard::SQUARE_NAMES[self.to_square as usize])?;
-        Ok(())
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
     }
 }
 
-pub mod piece {
-    use super::Piece;
+impl fmt::Debug for Square {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// error returned when a string isn't a square name such as "e4"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseSquareError;
 
-    pub const PAWN: Piece = 0;
-    pub const KNIGHT: Piece = 1;
-    pub const BISHOP: Piece = 2;
-    pub const ROOK: Piece = 3;
-    pub const QUEEN: Piece = 4;
-    pub const KING: Piece = 5;
-    pub const NONE: Piece = 6;
+impl fmt::Display for ParseSquareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid square name")
+    }
 }
 
-pub mod color {
-    use super::Color;
+impl std::error::Error for ParseSquareError {}
 
-    pub const WHITE: Color = 0;
-    pub const BLACK: Color = 1;
-    pub const NONE: Color = 2;
+impl FromStr for Square {
+    type Err = ParseSquareError;
+
+    fn from_str(s: &str) -> Result<Square, ParseSquareError> {
+        match s.as_bytes() {
+            [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => Ok(Square::from_coords_unchecked(file - b'a', rank - b'1')),
+            _ => Err(ParseSquareError)
+        }
+    }
 }
 
-pub mod square {
-    use super::Square;
-
-    pub const A1: Square = 0;
-    pub const B1: Square = 1;
-    pub const C1: Square = 2;
-    pub const D1: Square = 3;
-    pub const E1: Square = 4;
-    pub const F1: Square = 5;
-    pub const G1: Square = 6;
-    pub const H1: Square = 7;
-    pub const A2: Square = 8;
-    pub const B2: Square = 9;
-    pub const C2: Square = 10;
-    pub const D2: Square = 11;
-    pub const E2: Square = 12;
-    pub const F2: Square = 13;
-    pub const G2: Square = 14;
-    pub const H2: Square = 15;
-    pub const A3: Square = 16;
-    pub const B3: Square = 17;
-    pub const C3: Square = 18;
-    pub const D3: Square = 19;
-    pub const E3: Square = 20;
-    pub const F3: Square = 21;
-    pub const G3: Square = 22;
-    pub const H3: Square = 23;
-    pub const A4: Square = 24;
-    pub const B4: Square = 25;
-    pub const C4: Square = 26;
-    pub const D4: Square = 27;
-    pub const E4: Square = 28;
-    pub const F4: Square = 29;
-    pub const G4: Square = 30;
-    pub const H4: Square = 31;
-    pub const A5: Square = 32;
-    pub const B5: Square = 33;
-    pub const C5: Square = 34;
-    pub const D5: Square = 35;
-    pub const E5: Square = 36;
-    pub const F5: Square = 37;
-    pub const G5: Square = 38;
-    pub const H5: Square = 39;
-    pub const A6: Square = 40;
-    pub const B6: Square = 41;
-    pub const C6: Square = 42;
-    pub const D6: Square = 43;
-    pub const E6: Square = 44;
-    pub const F6: Square = 45;
-    pub const G6: Square = 46;
-    pub const H6: Square = 47;
-    pub const A7: Square = 48;
-    pub const B7: Square = 49;
-    pub const C7: Square = 50;
-    pub const D7: Square = 51;
-    pub const E7: Square = 52;
-    pub const F7: Square = 53;
-    pub const G7: Square = 54;
-    pub const H7: Square = 55;
-    pub const A8: Square = 56;
-    pub const B8: Square = 57;
-    pub const C8: Square = 58;
-    pub const D8: Square = 59;
-    pub const E8: Square = 60;
-    pub const F8: Square = 61;
-    pub const G8: Square = 62;
-    pub const H8: Square = 63;
-    pub const NONE: Square = 64;
-}
\ No newline at end of file
+// what a move does besides moving a piece from one square to another
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoveKind {
+    // quiet moves and captures, including pawn double pushes
+    Normal,
+    Promotion,
+    EnPassant,
+    // the king "captures" its own rook, to_square is the rook's square
+    Castle
+}
+
+// a move packed into 16 bits: from square in bits 0-5, to square in bits 6-11, kind in bits 12-13
+// and the promotion piece (knight to queen) in bits 14-15
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move(u16);
+
+impl Move {
+    #[inline]
+    const fn pack(from_square: Square, to_square: Square, kind: MoveKind, promotion: u16) -> Move {
+        Move(from_square.index() as u16 | (to_square.index() as u16) << 6 | (kind as u16) << 12 | promotion << 14)
+    }
+
+    // a regular move or capture
+    #[inline]
+    pub const fn new(from_square: Square, to_square: Square) -> Move {
+        Move::pack(from_square, to_square, MoveKind::Normal, 0)
+    }
+
+    // a pawn move to the last rank. promotion must be a knight, bishop, rook or queen
+    #[inline]
+    pub const fn new_promotion(from_square: Square, to_square: Square, promotion: Piece) -> Move {
+        debug_assert!(promotion.index() >= Piece::Knight.index() && promotion.index() <= Piece::Queen.index());
+        Move::pack(from_square, to_square, MoveKind::Promotion, (promotion as u16) - (Piece::Knight as u16))
+    }
+
+    #[inline]
+    pub const fn new_en_passant(from_square: Square, to_square: Square) -> Move {
+        Move::pack(from_square, to_square, MoveKind::EnPassant, 0)
+    }
+
+    // castling with the rook on rook_square
+    #[inline]
+    pub const fn new_castle(king_square: Square, rook_square: Square) -> Move {
+        Move::pack(king_square, rook_square, MoveKind::Castle, 0)
+    }
+
+    #[inline]
+    pub const fn from_square(self) -> Square {
+        Square::new_unchecked((self.0 & 0x3f) as u8)
+    }
+
+    #[inline]
+    pub const fn to_square(self) -> Square {
+        Square::new_unchecked((self.0 >> 6 & 0x3f) as u8)
+    }
+
+    #[inline]
+    pub const fn kind(self) -> MoveKind {
+        match self.0 >> 12 & 3 {
+            0 => MoveKind::Normal,
+            1 => MoveKind::Promotion,
+            2 => MoveKind::EnPassant,
+            _ => MoveKind::Castle
+        }
+    }
+
+    // piece a pawn promotes to, if this is a promotion
+    #[inline]
+    pub const fn promotion(self) -> Option<Piece> {
+        if self.is_promotion() {Some(self.promotion_piece())} else {None}
+    }
+
+    // decodes the promotion bits whatever the kind, for when the kind is already known to be a promotion
+    #[inline]
+    pub(crate) const fn promotion_piece(self) -> Piece {
+        match self.0 >> 14 {
+            0 => Piece::Knight,
+            1 => Piece::Bishop,
+            2 => Piece::Rook,
+            _ => Piece::Queen
+        }
+    }
+
+    #[inline]
+    pub const fn is_promotion(self) -> bool {
+        self.0 >> 12 & 3 == MoveKind::Promotion as u16
+    }
+
+    #[inline]
+    pub const fn is_en_passant(self) -> bool {
+        self.0 >> 12 & 3 == MoveKind::EnPassant as u16
+    }
+
+    #[inline]
+    pub const fn is_castle(self) -> bool {
+        self.0 >> 12 & 3 == MoveKind::Castle as u16
+    }
+
+    // long algebraic notation with castling written as the king capturing its rook, e.g. e2e4, e7e8q or e1h1. this is
+    // what UCI_Chess960 expects, but not plain UCI, so use Position::move_to_uci to write a move for a position
+    pub fn to_uci_chess960(&self) -> String {
+        let mut uci = String::with_capacity(5);
+        uci.push_str(self.from_square().name());
+        uci.push_str(self.to_square().name());
+        if let Some(promotion) = self.promotion() {
+            uci.push(promotion.symbol().to_ascii_lowercase());
+        }
+        uci
+    }
+}
+
+impl fmt::Debug for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Move")
+            .field("from_square", &self.from_square())
+            .field("to_square", &self.to_square())
+            .field("kind", &self.kind())
+            .field("promotion", &self.promotion())
+            .finish()
+    }
+}
+
+// long algebraic notation with castling written as the king's move, as in standard chess
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_castle() {
+            let (king_to, _) = crate::bitboard::castling_targets(self.from_square(), self.to_square());
+            return write!(f, "{}", Move::new(self.from_square(), king_to).to_uci_chess960());
+        }
+        write!(f, "{}", self.to_uci_chess960())
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt;
+
+use crate::types::*;
+use crate::bitboard::castling_targets;
+use crate::move_list::MoveList;
+use crate::position::Position;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UciMoveError {
+    // not of the form <from><to>[promotion], e.g. e2e4 or e7e8q
+    Invalid(String),
+    // well formed, but not a legal move in the position
+    Illegal(String),
+}
+
+impl fmt::Display for UciMoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UciMoveError::Invalid(uci) => write!(f, "invalid UCI move '{}'", uci),
+            UciMoveError::Illegal(uci) => write!(f, "illegal UCI move '{}'", uci)
+        }
+    }
+}
+
+impl std::error::Error for UciMoveError {}
+
+impl Position {
+    // write a move in long algebraic notation. castling is written as the king moving two squares in standard chess,
+    // and as the king capturing its rook in chess960 (as UCI_Chess960 expects)
+    pub fn move_to_uci(&self, mv: &Move) -> String {
+        if mv.is_castle() && !self.chess960 {
+            let (king_to, _) = castling_targets(mv.from_square(), mv.to_square());
+            return Move::new(mv.from_square(), king_to).to_uci_chess960();
+        }
+        mv.to_uci_chess960()
+    }
+
+    // parse a move in long algebraic notation (as used by UCI) by finding it among the legal moves
+    pub fn parse_uci_move(&self, uci: &str) -> Result<Move, UciMoveError> {
+        let invalid = || UciMoveError::Invalid(uci.to_string());
+
+        if !uci.is_ascii() || !(4..=5).contains(&uci.len()) {
+            return Err(invalid());
+        }
+
+        let from_square: Square = uci[0..2].parse().map_err(|_| invalid())?;
+        let to_square: Square = uci[2..4].parse().map_err(|_| invalid())?;
+        let promotion: Option<Piece> = match uci[4..].chars().next().map(|ch| ch.to_ascii_lowercase()) {
+            None => None,
+            Some('q') => Some(Piece::Queen),
+            Some('r') => Some(Piece::Rook),
+            Some('b') => Some(Piece::Bishop),
+            Some('n') => Some(Piece::Knight),
+            Some(_) => return Err(invalid())
+        };
+
+        let legal_moves: MoveList = self.legal_moves();
+
+        // castling can be written either as the king capturing its rook, or outside of chess960 as the king
+        // moving two squares
+        legal_moves.iter()
+            .find(|mv| mv.from_square() == from_square && mv.to_square() == to_square && mv.promotion() == promotion)
+            .or_else(|| legal_moves.iter().find(|mv| {
+                !self.chess960 && mv.is_castle() && mv.from_square() == from_square &&
+                castling_targets(mv.from_square(), mv.to_square()).0 == to_square && promotion.is_none()
+            }))
+            .copied()
+            .ok_or_else(|| UciMoveError::Illegal(uci.to_string()))
+    }
+}
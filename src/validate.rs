@@ -0,0 +1,151 @@
+use std::fmt;
+
+use crate::types::*;
+use crate::bitboard::*;
+use crate::fen::FenError;
+use crate::position::Position;
+
+// a reason why a position can't arise in a legal game (or would confuse move generation)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    // each side needs exactly one king
+    KingCount { color: Color, count: u8 },
+    PawnOnBackRank { square: Square },
+    // castling right set on a square without a rook of the right color, or without the king on the same back rank
+    InvalidCastlingRight { square: Square },
+    // en passant square that no pawn could have just skipped over
+    InvalidEpSquare { square: Square },
+    // the side that just moved left its own king in check
+    OpponentInCheck,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Violation::KingCount { color, count } =>
+                write!(f, "{} has {} kings", color, count),
+            Violation::PawnOnBackRank { square } =>
+                write!(f, "pawn on back rank at {}", square),
+            Violation::InvalidCastlingRight { square } =>
+                write!(f, "castling right for {} without a rook and king in place", square),
+            Violation::InvalidEpSquare { square } =>
+                write!(f, "en passant square {} does not follow a double pawn push", square),
+            Violation::OpponentInCheck =>
+                write!(f, "side not to move is in check")
+        }
+    }
+}
+
+// error from parsing a FEN and then validating the resulting position
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PositionError {
+    Fen(FenError),
+    Invalid(Vec<Violation>),
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PositionError::Fen(err) => write!(f, "{}", err),
+            PositionError::Invalid(violations) => {
+                write!(f, "invalid position: ")?;
+                for (i, violation) in violations.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", violation)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for PositionError {}
+
+impl From<FenError> for PositionError {
+    fn from(err: FenError) -> Self {
+        PositionError::Fen(err)
+    }
+}
+
+impl Position {
+    // parse a FEN and reject positions that fail validate()
+    pub fn from_fen_validated(fen: &str) -> Result<Position, PositionError> {
+        let pos = Position::from_fen(fen)?;
+
+        let violations = pos.validate();
+        if !violations.is_empty() {
+            return Err(PositionError::Invalid(violations));
+        }
+
+        Ok(pos)
+    }
+
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.validate().is_empty()
+    }
+
+    // returns every rule the position breaks. an empty list means the position is safe to generate moves for
+    pub fn validate(&self) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        // kings
+        for co in Color::ALL {
+            let count = popcount(self.kings & self.occupied[co.index()]);
+            if count != 1 {
+                violations.push(Violation::KingCount { color: co, count });
+            }
+        }
+
+        // pawns can never stand on the first or last rank
+        let mut back_rank_pawns: Bitboard = self.pawns & (BB_RANK_1 | BB_RANK_8);
+        while back_rank_pawns > 0 {
+            violations.push(Violation::PawnOnBackRank { square: pop_lsb(&mut back_rank_pawns) });
+        }
+
+        // castling rights need a rook of the right color on the square and the king on the same back rank
+        let mut castling_squares: Bitboard = self.castling_rights;
+        while castling_squares > 0 {
+            let sq: Square = pop_lsb(&mut castling_squares);
+
+            let valid = match sq.rank() {
+                0 => self.castling_right_valid(sq, Color::White, BB_RANK_1),
+                7 => self.castling_right_valid(sq, Color::Black, BB_RANK_8),
+                _ => false
+            };
+            if !valid {
+                violations.push(Violation::InvalidCastlingRight { square: sq });
+            }
+        }
+
+        // the en passant square must be empty, with the pawn that just double pushed in front of it
+        // and the square it started from empty
+        if let Some(ep_square) = self.ep_square {
+            let (ep_rank, forward): (u8, i8) = if self.turn == Color::White {(5, 8)} else {(2, -8)};
+
+            let valid = ep_square.rank() == ep_rank &&
+                self.piece_at(ep_square).is_none() &&
+                self.piece_at(ep_square.offset_unchecked(forward)).is_none() &&
+                self.piece_at(ep_square.offset_unchecked(-forward)) == Some(Piece::Pawn) &&
+                self.color_at(ep_square.offset_unchecked(-forward)) == Some(!self.turn);
+            if !valid {
+                violations.push(Violation::InvalidEpSquare { square: ep_square });
+            }
+        }
+
+        // the side that just moved can't be in check
+        let opponent_king: Bitboard = self.kings & self.occupied[(!self.turn).index()];
+        if popcount(opponent_king) == 1 && self.attackers_to(lsb(opponent_king), self.turn, self.occupied[Color::White.index()] | self.occupied[Color::Black.index()]) > 0 {
+            violations.push(Violation::OpponentInCheck);
+        }
+
+        violations
+    }
+
+    fn castling_right_valid(&self, sq: Square, co: Color, backrank: Bitboard) -> bool {
+        let own: Bitboard = self.occupied[co.index()];
+        self.rooks & own & square_bb(sq) > 0 && self.kings & own & backrank > 0
+    }
+}
@@ -0,0 +1,121 @@
+use crate::types::*;
+use crate::bitboard::*;
+use crate::position::Position;
+
+use lazy_static::lazy_static;
+
+lazy_static! {
+    pub static ref ZOBRIST: ZobristKeys = ZobristKeys::new();
+}
+
+// random keys xored together to form a position hash, see https://www.chessprogramming.org/Zobrist_Hashing
+pub struct ZobristKeys {
+    pieces: [[[u64; 64]; 6]; 2],
+    castling: [u64; 64],
+    ep_files: [u64; 8],
+    turn: u64
+}
+
+impl ZobristKeys {
+    pub fn new() -> Self {
+        // fixed seed so hashes are the same across runs
+        let mut rng = XorShift(0x9e3779b97f4a7c15);
+
+        let mut keys = Self {
+            pieces: [[[0; 64]; 6]; 2],
+            castling: [0; 64],
+            ep_files: [0; 8],
+            turn: 0
+        };
+
+        for color_keys in keys.pieces.iter_mut() {
+            for piece_keys in color_keys.iter_mut() {
+                for key in piece_keys.iter_mut() {
+                    *key = rng.next();
+                }
+            }
+        }
+        for key in keys.castling.iter_mut() {
+            *key = rng.next();
+        }
+        for key in keys.ep_files.iter_mut() {
+            *key = rng.next();
+        }
+        keys.turn = rng.next();
+
+        keys
+    }
+}
+
+impl Default for ZobristKeys {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// key lookups
+impl ZobristKeys {
+    #[inline]
+    pub fn piece(&self, sq: Square, pt: Piece, co: Color) -> u64 {
+        self.pieces[co.index()][pt.index()][sq.index()]
+    }
+
+    // combined key for every castling right in the bitboard
+    #[inline]
+    pub fn castling(&self, castling_rights: Bitboard) -> u64 {
+        let mut key: u64 = 0;
+        let mut rights: Bitboard = castling_rights;
+        while rights > 0 {
+            key ^= self.castling[pop_lsb(&mut rights).index()];
+        }
+        key
+    }
+
+    // key for an en passant square, or 0 if there is none
+    #[inline]
+    pub fn ep(&self, ep_square: Option<Square>) -> u64 {
+        ep_square.map_or(0, |sq| self.ep_files[sq.file() as usize])
+    }
+
+    #[inline]
+    pub fn turn(&self) -> u64 {
+        self.turn
+    }
+}
+
+impl Position {
+    // hash the whole position. make keeps self.hash equal to this incrementally
+    pub fn compute_hash_from_scratch(&self) -> u64 {
+        let mut hash: u64 = 0;
+
+        for co in Color::ALL {
+            let mut pieces: Bitboard = self.occupied[co.index()];
+            while pieces > 0 {
+                let sq: Square = pop_lsb(&mut pieces);
+                if let Some(pt) = self.piece_at(sq) {
+                    hash ^= ZOBRIST.piece(sq, pt, co);
+                }
+            }
+        }
+
+        hash ^= ZOBRIST.castling(self.castling_rights);
+        hash ^= ZOBRIST.ep(self.ep_square);
+        if self.turn == Color::Black {
+            hash ^= ZOBRIST.turn();
+        }
+
+        hash
+    }
+}
+
+// xorshift64* generator, only used to fill the key tables
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545f4914f6cdd1d)
+    }
+}